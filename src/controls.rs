@@ -0,0 +1,174 @@
+use std::time::{Duration, Instant};
+
+use crate::curses::{
+    box_, del_panel, delwin, doupdate, getmaxyx, hide_panel, mvwprintw, new_panel, newwin,
+    show_panel, stdscr, top_panel, update_panels, wattrset, werase, wgetch, wresize, wtimeout,
+    ToCurses, ERR, NULL,
+};
+
+use rustbonsai::domain::{
+    canvas::Attr,
+    keymap::{key_name, Action},
+};
+
+use crate::{guard, Tree};
+
+impl Tree {
+    // handle keys pressed during live growth without blocking, holding here while paused
+    pub(crate) fn poll_keys(&mut self) {
+        loop {
+            wtimeout(stdscr(), if self.paused { -1 } else { 0 });
+            let key = wgetch(stdscr());
+            if guard::caught().is_some() {
+                self.interrupt = Some(Action::Quit);
+                return;
+            }
+            if key == ERR {
+                return;
+            }
+            if let Some(action) = self.handle_key(key, false) {
+                self.interrupt = Some(action);
+                return;
+            }
+        }
+    }
+
+    // wait for a key that ends the current tree, or until the timeout runs out
+    pub(crate) fn wait_for_action(&mut self, timeout: Option<Duration>) -> Option<Action> {
+        let start = Instant::now();
+        loop {
+            let delay = match timeout {
+                Some(timeout) if !self.paused => {
+                    timeout.saturating_sub(start.elapsed()).as_millis() as i32
+                }
+                _ => -1,
+            };
+            wtimeout(stdscr(), delay);
+            let key = wgetch(stdscr());
+            if guard::caught().is_some() {
+                return Some(Action::Quit);
+            }
+            if key == ERR {
+                return None;
+            }
+            if let Some(action) = self.handle_key(key, !self.config.infinite) {
+                return Some(action);
+            }
+        }
+    }
+
+    // act on a key; actions that end the current tree are returned to the caller
+    fn handle_key(&mut self, key: i32, any_key_quits: bool) -> Option<Action> {
+        // screensaver quits on any keypress
+        if self.config.screensaver {
+            return Some(Action::Quit);
+        }
+        let Some(action) = self.config.keymap.action(key) else {
+            return any_key_quits.then_some(Action::Quit);
+        };
+
+        self.notice.clear();
+        match action {
+            Action::PanUp
+            | Action::PanDown
+            | Action::PanLeft
+            | Action::PanRight
+            | Action::ZoomOut
+            | Action::ZoomIn => {
+                if self.config.scroll {
+                    self.move_view(action);
+                }
+            }
+            Action::Quit | Action::NewTree | Action::Regrow => {
+                self.paused = false;
+                return Some(action);
+            }
+            Action::Pause => self.paused = !self.paused,
+            Action::Faster => {
+                self.config.time_step /= 2;
+                self.notice = format!("{}ms per step", self.config.time_step);
+            }
+            Action::Slower => {
+                self.config.time_step = (self.config.time_step * 2).max(1);
+                self.notice = format!("{}ms per step", self.config.time_step);
+            }
+            Action::Save => {
+                self.notice = match self.save_progress() {
+                    Ok(()) => format!("saved to {}", self.config.save_file),
+                    Err(why) => why.to_string(),
+                };
+            }
+            Action::Help => self.toggle_help(),
+            // only once the tree is grown, with --prune
+            Action::Cut | Action::Undo | Action::Redo => {}
+        }
+        self.draw_status();
+        update_panels();
+        doupdate();
+        None
+    }
+
+    fn toggle_help(&mut self) {
+        if !self.objects.help_panel.is_null() {
+            del_panel(self.objects.help_panel);
+            delwin(self.objects.help_win);
+            self.objects.help_panel = NULL;
+            return;
+        }
+
+        let mut lines: Vec<String> = self
+            .config
+            .keymap
+            .bindings()
+            .iter()
+            .filter(|(_, action)| {
+                (self.config.scroll || self.config.prune || !action.moves_view())
+                    && (self.config.prune || !action.prunes())
+            })
+            .map(|&(key, action)| format!("{:>5}  {}", key_name(key), action.description()))
+            .collect();
+        lines.push(String::new());
+        lines.push(format!("seed: {}", self.config.seed));
+
+        let width = lines.iter().map(String::len).max().unwrap_or(0) as i32 + 4;
+        let height = lines.len() as i32 + 2;
+        let mut rows = 0;
+        let mut cols = 0;
+        getmaxyx(stdscr(), &mut rows, &mut cols);
+
+        // centered over everything else
+        self.objects.help_win = newwin(height, width, (rows - height) / 2, (cols - width) / 2);
+        box_(self.objects.help_win, 0, 0);
+        mvwprintw(self.objects.help_win, 0, 2, " keys ");
+        for (y, line) in (1..).zip(&lines) {
+            mvwprintw(self.objects.help_win, y, 2, line);
+        }
+        self.objects.help_panel = new_panel(self.objects.help_win);
+    }
+
+    // status line in the top left corner: pause state, seed and the last notice
+    pub(crate) fn draw_status(&mut self) {
+        let mut parts = Vec::new();
+        if self.paused {
+            parts.push("paused".to_owned());
+        }
+        if self.paused || self.config.show_seed {
+            parts.push(format!("seed {}", self.config.seed));
+        }
+        if !self.notice.is_empty() {
+            parts.push(self.notice.clone());
+        }
+
+        if parts.is_empty() {
+            hide_panel(self.objects.status_panel);
+            return;
+        }
+        let text = format!(" {} ", parts.join("  "));
+        wresize(self.objects.status_win, 1, text.len() as i32);
+        werase(self.objects.status_win);
+        wattrset(self.objects.status_win, Attr::new(8, false).to_curses());
+        mvwprintw(self.objects.status_win, 0, 0, &text);
+        show_panel(self.objects.status_panel);
+        top_panel(self.objects.status_panel);
+    }
+}
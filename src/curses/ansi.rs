@@ -0,0 +1,845 @@
+// the curses calls the binary makes, done in Rust by writing ANSI escape sequences to the
+// terminal. windows are cell buffers and panels a stack of them that doupdate composes into
+// one frame, writing only the cells that changed since the last one. there is no terminfo,
+// anything that understands xterm sequences will do
+#![allow(non_camel_case_types, non_snake_case, clippy::upper_case_acronyms)]
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt::Write as _,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    os::fd::{AsRawFd, RawFd},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use unicode_width::UnicodeWidthChar;
+
+pub type attr_t = u32;
+
+pub const OK: i32 = 0;
+pub const ERR: i32 = -1;
+pub const COLOR_BLACK: i16 = 0;
+
+// the key codes ncurses uses, so keymaps work with either backend
+pub const KEY_DOWN: i32 = 0o402;
+pub const KEY_UP: i32 = 0o403;
+pub const KEY_LEFT: i32 = 0o404;
+pub const KEY_RIGHT: i32 = 0o405;
+pub const KEY_ENTER: i32 = 0o527;
+
+const ESCAPE: u8 = 27;
+// milliseconds to wait for the rest of an escape sequence before taking a lone escape
+const ESCAPE_DELAY: i32 = 25;
+
+const BOLD: attr_t = 1 << 21;
+const REVERSE: attr_t = 1 << 18;
+
+pub enum CURSOR_VISIBILITY {
+    CURSOR_INVISIBLE,
+    CURSOR_VISIBLE,
+    CURSOR_VERY_VISIBLE,
+}
+
+// a window or a panel, numbered from 1
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Handle(usize);
+
+impl Handle {
+    pub fn is_null(self) -> bool {
+        self.0 == 0
+    }
+}
+
+pub type WINDOW = Handle;
+pub type PANEL = Handle;
+
+// an absent window or panel
+pub const NULL: Handle = Handle(0);
+const STDSCR: Handle = Handle(1);
+
+pub fn COLOR_PAIR(n: i16) -> attr_t {
+    (n as attr_t & 0xff) << 8
+}
+
+pub fn A_BOLD() -> attr_t {
+    BOLD
+}
+
+pub fn A_REVERSE() -> attr_t {
+    REVERSE
+}
+
+type Glyph = (char, attr_t);
+const BLANK: Glyph = (' ', 0);
+// the right half of a wide character, which the cell before it covers
+const WIDE_TAIL: char = '\0';
+
+// set by SIGWINCH, the screen takes the new terminal size the next time it is used
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_resize(_: libc::c_int) {
+    RESIZED.store(true, Ordering::SeqCst);
+}
+
+struct Window {
+    y: i32,
+    x: i32,
+    height: i32,
+    width: i32,
+    // attributes of text written from now on
+    attr: attr_t,
+    cells: Vec<Glyph>,
+}
+
+impl Window {
+    fn new(height: i32, width: i32, y: i32, x: i32) -> Self {
+        let (height, width) = (height.max(0), width.max(0));
+        Self {
+            y,
+            x,
+            height,
+            width,
+            attr: 0,
+            cells: vec![BLANK; (height * width) as usize],
+        }
+    }
+
+    fn get(&self, y: i32, x: i32) -> Option<Glyph> {
+        let inside = (0..self.height).contains(&y) && (0..self.width).contains(&x);
+        inside.then(|| self.cells[(y * self.width + x) as usize])
+    }
+
+    fn set(&mut self, y: i32, x: i32, glyph: Glyph) {
+        if (0..self.height).contains(&y) && (0..self.width).contains(&x) {
+            self.cells[(y * self.width + x) as usize] = glyph;
+        }
+    }
+
+    // text runs off the right edge rather than wrapping. wide characters take two cells as
+    // they do on the terminal, and one cut in half by the text is blanked
+    fn put_str(&mut self, y: i32, x: i32, text: &str) -> i32 {
+        if self.get(y, x).is_none() {
+            return ERR;
+        }
+        let mut x = x;
+        for ch in text.chars() {
+            // combining marks and control characters take no cell of their own
+            let width = match ch.width() {
+                Some(width @ 1..=2) => width as i32,
+                _ => continue,
+            };
+            if x + width > self.width {
+                break;
+            }
+            if self.get(y, x).is_some_and(|glyph| glyph.0 == WIDE_TAIL) {
+                self.set(y, x - 1, (' ', self.attr));
+            }
+            if self
+                .get(y, x + width)
+                .is_some_and(|glyph| glyph.0 == WIDE_TAIL)
+            {
+                self.set(y, x + width, (' ', self.attr));
+            }
+            self.set(y, x, (ch, self.attr));
+            if width == 2 {
+                self.set(y, x + 1, (WIDE_TAIL, self.attr));
+            }
+            x += width;
+        }
+        OK
+    }
+
+    fn resize(&mut self, height: i32, width: i32) {
+        let mut resized = Self::new(height, width, self.y, self.x);
+        resized.attr = self.attr;
+        for y in 0..resized.height {
+            for x in 0..resized.width {
+                if let Some(glyph) = self.get(y, x) {
+                    resized.set(y, x, glyph);
+                }
+            }
+        }
+        *self = resized;
+    }
+}
+
+struct Panel {
+    window: WINDOW,
+    hidden: bool,
+}
+
+struct Screen {
+    input: RawFd,
+    output: Box<dyn Write>,
+    // where the terminal size is asked for
+    size_fd: RawFd,
+    // terminal modes from before initscr, restored by endwin
+    shell: Option<libc::termios>,
+    saved: Option<libc::termios>,
+    rows: i32,
+    cols: i32,
+    // by handle - 1, stdscr first
+    windows: Vec<Option<Window>>,
+    panels: Vec<Option<Panel>>,
+    // panels bottom to top
+    stack: Vec<PANEL>,
+    // (foreground, background) of each color pair, -1 for the terminal's default
+    pairs: Vec<(i16, i16)>,
+    // wgetch timeout in milliseconds, -1 to block
+    delay: i32,
+    keypad: bool,
+    // bytes read but not yet returned by wgetch
+    pending: VecDeque<u8>,
+    // what the terminal shows, None where it is unknown
+    shown: Vec<Option<Glyph>>,
+}
+
+thread_local! {
+    static SCREEN: RefCell<Option<Screen>> = const { RefCell::new(None) };
+}
+
+fn with<R>(f: impl FnOnce(&mut Screen) -> R) -> R {
+    SCREEN.with(|screen| {
+        let mut screen = screen.borrow_mut();
+        let screen = screen.as_mut().expect("initscr was not called");
+        screen.check_resize();
+        f(screen)
+    })
+}
+
+// for putting the terminal back while a panic may have left the screen borrowed
+fn try_with(f: impl FnOnce(&mut Screen) -> i32) -> i32 {
+    SCREEN.with(|screen| match screen.try_borrow_mut() {
+        Ok(mut screen) => screen.as_mut().map_or(ERR, f),
+        Err(_) => ERR,
+    })
+}
+
+fn with_window<R>(win: WINDOW, default: R, f: impl FnOnce(&mut Window) -> R) -> R {
+    with(|screen| screen.window(win).map_or(default, f))
+}
+
+impl Screen {
+    fn open(input: RawFd, output: Box<dyn Write>, size_fd: RawFd) -> Self {
+        let (rows, cols) = terminal_size(size_fd);
+        let mut screen = Self {
+            input,
+            output,
+            size_fd,
+            shell: get_mode(input),
+            saved: None,
+            rows,
+            cols,
+            windows: vec![Some(Window::new(rows, cols, 0, 0))],
+            panels: Vec::new(),
+            stack: Vec::new(),
+            pairs: vec![(-1, -1); 256],
+            delay: -1,
+            keypad: false,
+            pending: VecDeque::new(),
+            shown: vec![None; (rows * cols) as usize],
+        };
+        // the alternate screen keeps the shell's scrollback as it was
+        screen.emit("\x1b[?1049h\x1b[H\x1b[2J");
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_resize as extern "C" fn(libc::c_int) as usize;
+            libc::sigemptyset(&mut action.sa_mask);
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigaction(libc::SIGWINCH, &action, std::ptr::null_mut());
+        }
+        screen
+    }
+
+    // after a resize stdscr takes the new size, as in ncurses, and the whole terminal is
+    // repainted since it has rewrapped or cut off what it showed
+    fn check_resize(&mut self) {
+        if !RESIZED.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        let (rows, cols) = terminal_size(self.size_fd);
+        (self.rows, self.cols) = (rows, cols);
+        if let Some(stdscr) = self.window(STDSCR) {
+            stdscr.resize(rows, cols);
+        }
+        self.shown = vec![None; (rows * cols) as usize];
+        self.emit("\x1b[0m\x1b[2J");
+        // a wait for a key could go on a while, the screen is not left blank meanwhile
+        let frame = self.compose(true);
+        self.show(&frame);
+    }
+
+    fn emit(&mut self, text: &str) {
+        // a terminal that went away leaves nothing to report to
+        let _ = self.output.write_all(text.as_bytes());
+        let _ = self.output.flush();
+    }
+
+    fn window(&mut self, win: WINDOW) -> Option<&mut Window> {
+        self.windows.get_mut(win.0.checked_sub(1)?)?.as_mut()
+    }
+
+    fn panel(&mut self, pan: PANEL) -> Option<&mut Panel> {
+        self.panels.get_mut(pan.0.checked_sub(1)?)?.as_mut()
+    }
+
+    fn update_mode(&mut self, f: impl FnOnce(&mut libc::termios)) -> i32 {
+        let Some(mut mode) = get_mode(self.input) else {
+            return ERR;
+        };
+        f(&mut mode);
+        set_mode(self.input, &mode)
+    }
+
+    // stdscr, with the visible panels stacked over it when with_panels is set
+    fn compose(&self, with_panels: bool) -> Vec<Glyph> {
+        let mut frame = vec![BLANK; (self.rows * self.cols) as usize];
+        let panels = self
+            .stack
+            .iter()
+            .filter(|_| with_panels)
+            .filter_map(|pan| self.panels[pan.0 - 1].as_ref())
+            .filter(|panel| !panel.hidden)
+            .map(|panel| panel.window);
+        for win in std::iter::once(STDSCR).chain(panels) {
+            let Some(Some(window)) = self.windows.get(win.0 - 1) else {
+                continue;
+            };
+            for y in 0..window.height {
+                for x in 0..window.width {
+                    let (row, col) = (window.y + y, window.x + x);
+                    if (0..self.rows).contains(&row) && (0..self.cols).contains(&col) {
+                        frame[(row * self.cols + col) as usize] =
+                            window.cells[(y * window.width + x) as usize];
+                    }
+                }
+            }
+        }
+        frame
+    }
+
+    // write the cells that differ from what the terminal shows
+    fn show(&mut self, frame: &[Glyph]) {
+        let mut out = String::new();
+        let mut cursor = None;
+        let mut current = None;
+        for (i, &glyph) in frame.iter().enumerate() {
+            let (y, x) = (i as i32 / self.cols, i as i32 % self.cols);
+            // a wide character whose right half is still on screen, panels can cut it off
+            let tail = (x + 1 < self.cols && frame[i + 1].0 == WIDE_TAIL).then_some(i + 1);
+            let wide = glyph.0 != WIDE_TAIL && glyph.0.width() == Some(2) && tail.is_some();
+            let unchanged = |i: usize| self.shown[i] == Some(frame[i]);
+            if unchanged(i) && !(wide && tail.is_some_and(|tail| !unchanged(tail))) {
+                continue;
+            }
+            self.shown[i] = Some(glyph);
+            // the wide character before writes this cell too
+            let covered = x > 0 && frame[i - 1].0.width() == Some(2);
+            if glyph.0 == WIDE_TAIL && covered {
+                continue;
+            }
+            if cursor != Some(i) {
+                let _ = write!(out, "\x1b[{};{}H", y + 1, x + 1);
+            }
+            if current != Some(glyph.1) {
+                current = Some(glyph.1);
+                out += &self.sgr(glyph.1);
+            }
+            // halves of a wide character that lost the other half show as blanks
+            let width = if wide {
+                out.push(glyph.0);
+                2
+            } else if glyph.0 == WIDE_TAIL || glyph.0.width() == Some(2) {
+                out.push(' ');
+                1
+            } else {
+                out.push(glyph.0);
+                1
+            };
+            // the cursor stays on the last column at the end of a row
+            cursor = (x + width < self.cols).then_some(i + width as usize);
+        }
+        if !out.is_empty() {
+            out += "\x1b[0m";
+            self.emit(&out);
+        }
+    }
+
+    // select graphic rendition for an attribute, starting from a reset
+    fn sgr(&self, attr: attr_t) -> String {
+        let (fg, bg) = self.pairs[((attr >> 8) & 0xff) as usize];
+        let mut codes = vec!["0".to_owned()];
+        if attr & BOLD != 0 {
+            codes.push("1".to_owned());
+        }
+        if attr & REVERSE != 0 {
+            codes.push("7".to_owned());
+        }
+        codes.extend(color_code(fg, 30, 90, 38));
+        codes.extend(color_code(bg, 40, 100, 48));
+        format!("\x1b[{}m", codes.join(";"))
+    }
+
+    fn read_byte(&mut self, timeout: i32) -> Option<u8> {
+        if let Some(byte) = self.pending.pop_front() {
+            return Some(byte);
+        }
+        let mut poll = libc::pollfd {
+            fd: self.input,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let deadline = Instant::now() + Duration::from_millis(timeout.max(0) as u64);
+        loop {
+            let left = deadline
+                .saturating_duration_since(Instant::now())
+                .as_millis() as i32;
+            let ready = unsafe { libc::poll(&mut poll, 1, if timeout < 0 { -1 } else { left }) };
+            if ready > 0 {
+                break;
+            }
+            // a resize interrupts the wait, other signals end it for the caller to see
+            if ready == 0 || !RESIZED.load(Ordering::SeqCst) {
+                return None;
+            }
+            self.check_resize();
+        }
+        let mut buf = [0_u8; 64];
+        let read = unsafe { libc::read(self.input, buf.as_mut_ptr().cast(), buf.len()) };
+        if read <= 0 {
+            return None;
+        }
+        self.pending.extend(&buf[..read as usize]);
+        self.pending.pop_front()
+    }
+
+    fn getch(&mut self) -> i32 {
+        let Some(byte) = self.read_byte(self.delay) else {
+            return ERR;
+        };
+        if byte != ESCAPE || !self.keypad {
+            return i32::from(byte);
+        }
+
+        // arrow keys arrive as ESC [ A, or ESC O A in application mode
+        let Some(intro) = self.read_byte(ESCAPE_DELAY) else {
+            return i32::from(ESCAPE);
+        };
+        let code = match intro {
+            b'[' | b'O' => self.read_byte(ESCAPE_DELAY),
+            _ => None,
+        };
+        match code {
+            Some(b'A') => KEY_UP,
+            Some(b'B') => KEY_DOWN,
+            Some(b'C') => KEY_RIGHT,
+            Some(b'D') => KEY_LEFT,
+            Some(b'M') => KEY_ENTER,
+            _ => {
+                // not a key we know, hand the bytes back one at a time
+                if let Some(code) = code {
+                    self.pending.push_front(code);
+                }
+                self.pending.push_front(intro);
+                i32::from(ESCAPE)
+            }
+        }
+    }
+}
+
+// sgr parameters for a color: normal, bright or one of the 256
+fn color_code(color: i16, normal: i16, bright: i16, extended: i16) -> Option<String> {
+    match color {
+        0..=7 => Some((normal + color).to_string()),
+        8..=15 => Some((bright + color - 8).to_string()),
+        16..=255 => Some(format!("{extended};5;{color}")),
+        _ => None,
+    }
+}
+
+fn get_mode(fd: RawFd) -> Option<libc::termios> {
+    let mut mode = unsafe { std::mem::zeroed::<libc::termios>() };
+    (unsafe { libc::tcgetattr(fd, &mut mode) } == 0).then_some(mode)
+}
+
+fn set_mode(fd: RawFd, mode: &libc::termios) -> i32 {
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, mode) } == 0 {
+        OK
+    } else {
+        ERR
+    }
+}
+
+// (rows, cols) of the terminal, or what LINES and COLUMNS say if it wont tell
+fn terminal_size(fd: RawFd) -> (i32, i32) {
+    let mut size = unsafe { std::mem::zeroed::<libc::winsize>() };
+    if unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) } == 0 && size.ws_row > 0 {
+        return (i32::from(size.ws_row), i32::from(size.ws_col));
+    }
+    let var = |name: &str, default| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    };
+    (var("LINES", 24), var("COLUMNS", 80))
+}
+
+fn start(open: impl FnOnce() -> Screen) -> WINDOW {
+    SCREEN.with(|screen| {
+        let mut screen = screen.borrow_mut();
+        if screen.is_none() {
+            *screen = Some(open());
+        }
+    });
+    STDSCR
+}
+
+pub fn initscr() -> WINDOW {
+    start(|| {
+        let stdout = io::stdout();
+        let fd = stdout.as_raw_fd();
+        Screen::open(io::stdin().as_raw_fd(), Box::new(stdout), fd)
+    })
+}
+
+// curses on /dev/tty instead of stdin and stdout, leaving stdin free for piped input
+pub fn newterm_tty() -> WINDOW {
+    start(|| {
+        let tty: File = match OpenOptions::new().read(true).write(true).open("/dev/tty") {
+            Ok(tty) => tty,
+            Err(_) => {
+                eprintln!("couldnt open /dev/tty");
+                std::process::exit(1);
+            }
+        };
+        let fd = tty.as_raw_fd();
+        // the file lives on as the output, which keeps fd open for reading too
+        Screen::open(fd, Box::new(tty), fd)
+    })
+}
+
+pub fn stdscr() -> WINDOW {
+    STDSCR
+}
+
+pub fn endwin() -> i32 {
+    try_with(|screen| {
+        if let Some(shell) = screen.shell {
+            set_mode(screen.input, &shell);
+        }
+        screen.emit("\x1b[0m\x1b[?25h\x1b[?1049l");
+        screen.shown.fill(None);
+        OK
+    })
+}
+
+pub fn savetty() -> i32 {
+    with(|screen| {
+        screen.saved = get_mode(screen.input);
+        if screen.saved.is_some() {
+            OK
+        } else {
+            ERR
+        }
+    })
+}
+
+pub fn resetty() -> i32 {
+    try_with(|screen| match screen.saved {
+        Some(saved) => set_mode(screen.input, &saved),
+        None => ERR,
+    })
+}
+
+pub fn noecho() -> i32 {
+    with(|screen| screen.update_mode(|mode| mode.c_lflag &= !libc::ECHO))
+}
+
+// keys arrive as they are pressed, ctrl-c still interrupts
+pub fn cbreak() -> i32 {
+    with(|screen| {
+        screen.update_mode(|mode| {
+            mode.c_lflag &= !libc::ICANON;
+            mode.c_cc[libc::VMIN] = 1;
+            mode.c_cc[libc::VTIME] = 0;
+        })
+    })
+}
+
+pub fn curs_set(visibility: CURSOR_VISIBILITY) -> Option<CURSOR_VISIBILITY> {
+    let sequence = match visibility {
+        CURSOR_VISIBILITY::CURSOR_INVISIBLE => "\x1b[?25l",
+        _ => "\x1b[?25h",
+    };
+    try_with(|screen| {
+        screen.emit(sequence);
+        OK
+    });
+    None
+}
+
+pub fn nodelay(_win: WINDOW, bf: bool) -> i32 {
+    with(|screen| screen.delay = if bf { 0 } else { -1 });
+    OK
+}
+
+pub fn wtimeout(_win: WINDOW, delay: i32) {
+    with(|screen| screen.delay = delay);
+}
+
+pub fn keypad(_win: WINDOW, bf: bool) -> i32 {
+    with(|screen| screen.keypad = bf);
+    OK
+}
+
+pub fn wgetch(_win: WINDOW) -> i32 {
+    with(Screen::getch)
+}
+
+pub fn has_colors() -> bool {
+    true
+}
+
+pub fn start_color() -> i32 {
+    OK
+}
+
+pub fn use_default_colors() -> i32 {
+    OK
+}
+
+// terminals that say they do 256 colors get them, everything else is taken for 8
+pub fn COLORS() -> i32 {
+    let term = std::env::var("TERM").unwrap_or_default();
+    if term.contains("256color") || std::env::var("COLORTERM").is_ok() {
+        256
+    } else {
+        8
+    }
+}
+
+pub fn init_pair(pair: i16, f: i16, b: i16) -> i32 {
+    with(|screen| match screen.pairs.get_mut(pair as usize) {
+        Some(colors) => {
+            *colors = (f, b);
+            OK
+        }
+        None => ERR,
+    })
+}
+
+// put a new window or panel in the first slot freed, so trees coming and going in infinite
+// mode dont pile up slots
+fn reuse<T>(slots: &mut Vec<Option<T>>, item: T) -> Handle {
+    if let Some(i) = slots.iter().position(Option::is_none) {
+        slots[i] = Some(item);
+        return Handle(i + 1);
+    }
+    slots.push(Some(item));
+    Handle(slots.len())
+}
+
+// like ncurses, a zero size reaches to the bottom or right edge of the screen
+pub fn newwin(lines: i32, cols: i32, y: i32, x: i32) -> WINDOW {
+    with(|screen| {
+        let lines = if lines == 0 { screen.rows - y } else { lines };
+        let cols = if cols == 0 { screen.cols - x } else { cols };
+        reuse(&mut screen.windows, Window::new(lines, cols, y, x))
+    })
+}
+
+pub fn delwin(win: WINDOW) -> i32 {
+    if win.is_null() || win == STDSCR {
+        return ERR;
+    }
+    with(|screen| match screen.windows.get_mut(win.0 - 1) {
+        Some(window @ Some(_)) => {
+            *window = None;
+            OK
+        }
+        _ => ERR,
+    })
+}
+
+pub fn wresize(win: WINDOW, lines: i32, cols: i32) -> i32 {
+    with_window(win, ERR, |window| {
+        window.resize(lines, cols);
+        OK
+    })
+}
+
+pub fn getmaxyx(win: WINDOW, y: &mut i32, x: &mut i32) {
+    (*y, *x) = with_window(win, (ERR, ERR), |window| (window.height, window.width));
+}
+
+pub fn werase(win: WINDOW) -> i32 {
+    with_window(win, ERR, |window| {
+        window.cells.fill(BLANK);
+        OK
+    })
+}
+
+// erase stdscr and have the next refresh repaint the whole terminal
+pub fn clear() -> i32 {
+    with(|screen| {
+        screen.shown.fill(None);
+        screen.emit("\x1b[0m\x1b[2J");
+    });
+    werase(STDSCR)
+}
+
+pub fn wattrset(win: WINDOW, attr: attr_t) -> i32 {
+    with_window(win, ERR, |window| {
+        window.attr = attr;
+        OK
+    })
+}
+
+pub fn mvwaddstr(win: WINDOW, y: i32, x: i32, s: &str) -> i32 {
+    with_window(win, ERR, |window| window.put_str(y, x, s))
+}
+
+// there is no formatting, the text is written as it is
+pub fn mvwprintw(win: WINDOW, y: i32, x: i32, s: &str) -> i32 {
+    mvwaddstr(win, y, x, s)
+}
+
+// a border of line drawing characters around the edge of the window
+pub fn box_(win: WINDOW, _verch: attr_t, _horch: attr_t) -> i32 {
+    with_window(win, ERR, |window| {
+        let (bottom, right) = (window.height - 1, window.width - 1);
+        let glyph = |ch| (ch, window.attr);
+        let mut border = Vec::new();
+        for x in 1..right {
+            border.push((0, x, glyph('─')));
+            border.push((bottom, x, glyph('─')));
+        }
+        for y in 1..bottom {
+            border.push((y, 0, glyph('│')));
+            border.push((y, right, glyph('│')));
+        }
+        border.push((0, 0, glyph('┌')));
+        border.push((0, right, glyph('┐')));
+        border.push((bottom, 0, glyph('└')));
+        border.push((bottom, right, glyph('┘')));
+        for (y, x, glyph) in border {
+            window.set(y, x, glyph);
+        }
+        OK
+    })
+}
+
+// copy where the windows overlap on screen, overlay leaves out blanks
+fn copy_window(src: WINDOW, dst: WINDOW, blanks: bool) -> i32 {
+    with(|screen| {
+        let Some(source) = screen.window(src) else {
+            return ERR;
+        };
+        let (y, x) = (source.y, source.x);
+        let cells: Vec<_> = (0..source.height)
+            .flat_map(|row| (0..source.width).map(move |col| (row, col)))
+            .filter_map(|(row, col)| Some((y + row, x + col, source.get(row, col)?)))
+            .filter(|&(_, _, glyph)| blanks || glyph.0 != ' ')
+            .collect();
+        let Some(dest) = screen.window(dst) else {
+            return ERR;
+        };
+        for (row, col, glyph) in cells {
+            dest.set(row - dest.y, col - dest.x, glyph);
+        }
+        OK
+    })
+}
+
+pub fn overlay(src: WINDOW, dst: WINDOW) -> i32 {
+    copy_window(src, dst, false)
+}
+
+pub fn overwrite(src: WINDOW, dst: WINDOW) -> i32 {
+    copy_window(src, dst, true)
+}
+
+// draw stdscr over everything, as a refresh of stdscr does in ncurses
+pub fn refresh() -> i32 {
+    with(|screen| {
+        let frame = screen.compose(false);
+        screen.show(&frame);
+        OK
+    })
+}
+
+// a new panel goes on top of the stack
+pub fn new_panel(win: WINDOW) -> PANEL {
+    with(|screen| {
+        if screen.window(win).is_none() {
+            return NULL;
+        }
+        let pan = reuse(
+            &mut screen.panels,
+            Panel {
+                window: win,
+                hidden: false,
+            },
+        );
+        screen.stack.push(pan);
+        pan
+    })
+}
+
+pub fn del_panel(pan: PANEL) -> i32 {
+    if pan.is_null() {
+        return ERR;
+    }
+    with(|screen| {
+        screen.stack.retain(|&other| other != pan);
+        match screen.panels.get_mut(pan.0 - 1) {
+            Some(panel @ Some(_)) => {
+                *panel = None;
+                OK
+            }
+            _ => ERR,
+        }
+    })
+}
+
+pub fn hide_panel(pan: PANEL) -> i32 {
+    with(|screen| match screen.panel(pan) {
+        Some(panel) => {
+            panel.hidden = true;
+            OK
+        }
+        None => ERR,
+    })
+}
+
+// shown panels go on top, as in ncurses
+pub fn show_panel(pan: PANEL) -> i32 {
+    with(|screen| {
+        let Some(panel) = screen.panel(pan) else {
+            return ERR;
+        };
+        panel.hidden = false;
+        screen.stack.retain(|&other| other != pan);
+        screen.stack.push(pan);
+        OK
+    })
+}
+
+pub fn top_panel(pan: PANEL) -> i32 {
+    show_panel(pan)
+}
+
+// panels are composed when doupdate draws them
+pub fn update_panels() {}
+
+pub fn doupdate() -> i32 {
+    with(|screen| {
+        let frame = screen.compose(true);
+        screen.show(&frame);
+        OK
+    })
+}
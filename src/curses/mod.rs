@@ -0,0 +1,44 @@
+// the terminal backend. ncurses by default, the ansi feature swaps in a pure-Rust
+// replacement with the same calls so the binary builds without linking a C library
+#[cfg(not(any(feature = "ncurses", feature = "ansi")))]
+compile_error!("enable a terminal backend, either the ncurses or the ansi feature");
+
+#[cfg(feature = "ansi")]
+mod ansi;
+
+#[cfg(feature = "ansi")]
+pub use self::ansi::*;
+#[cfg(all(feature = "ncurses", not(feature = "ansi")))]
+pub use ncurses::*;
+
+use rustbonsai::domain::canvas::Attr;
+
+// an absent window or panel
+#[cfg(all(feature = "ncurses", not(feature = "ansi")))]
+pub const NULL: WINDOW = std::ptr::null_mut();
+
+// curses on /dev/tty instead of stdin and stdout, leaving stdin free for piped input
+#[cfg(all(feature = "ncurses", not(feature = "ansi")))]
+pub fn newterm_tty() -> WINDOW {
+    let tty = unsafe { libc::fopen(c"/dev/tty".as_ptr(), c"r+".as_ptr()) };
+    if tty.is_null() {
+        eprintln!("couldnt open /dev/tty");
+        std::process::exit(1);
+    }
+    newterm(None, tty as ll::FILE_p, tty as ll::FILE_p);
+    stdscr()
+}
+
+pub trait ToCurses {
+    fn to_curses(self) -> attr_t;
+}
+
+impl ToCurses for Attr {
+    fn to_curses(self) -> attr_t {
+        if self.bold {
+            COLOR_PAIR(self.color) | A_BOLD()
+        } else {
+            COLOR_PAIR(self.color)
+        }
+    }
+}
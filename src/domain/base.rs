@@ -0,0 +1,50 @@
+use super::{canvas::Attr, config::BaseType};
+
+// (width, height) of the window holding the base
+pub fn base_size(base_type: &BaseType) -> (i32, i32) {
+    match base_type {
+        BaseType::None => (0, 0),
+        BaseType::Small => (15, 3),
+        BaseType::Big => (31, 4),
+    }
+}
+
+// the line a forest stands on instead of pots
+pub const GROUND: Attr = Attr::new(2, false);
+
+pub fn ground(width: i32) -> String {
+    "_".repeat(width.max(0) as usize)
+}
+
+// pieces of the base as (y, x, text, attr), relative to the base window
+pub fn base_art(base_type: &BaseType) -> &'static [(i32, i32, &'static str, Attr)] {
+    const GRAY: Attr = Attr::new(8, false);
+    const GREEN: Attr = Attr::new(2, false);
+    const YELLOW: Attr = Attr::new(11, false);
+    const BOLD_GRAY: Attr = Attr::new(8, true);
+    const BOLD_GREEN: Attr = Attr::new(2, true);
+    const BOLD_YELLOW: Attr = Attr::new(11, true);
+
+    match base_type {
+        BaseType::None => &[],
+        BaseType::Small => &[
+            (0, 0, "(", GRAY),
+            (0, 1, "---", GREEN),
+            (0, 4, "./~~~\\.", YELLOW),
+            (0, 11, "---", GREEN),
+            (0, 14, ")", GRAY),
+            (1, 0, " (           ) ", GRAY),
+            (2, 0, "  (_________)  ", GRAY),
+        ],
+        BaseType::Big => &[
+            (0, 0, ":", BOLD_GRAY),
+            (0, 1, "___________", BOLD_GREEN),
+            (0, 12, "./~~~\\.", BOLD_YELLOW),
+            (0, 19, "___________", BOLD_GREEN),
+            (0, 30, ":", BOLD_GRAY),
+            (1, 0, " \\                           / ", BOLD_GRAY),
+            (2, 0, "  \\_________________________/ ", BOLD_GRAY),
+            (3, 0, "  (_)                     (_)", BOLD_GRAY),
+        ],
+    }
+}
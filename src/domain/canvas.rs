@@ -0,0 +1,86 @@
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Attr {
+    pub color: i16,
+    pub bold: bool,
+}
+
+impl Attr {
+    pub const fn new(color: i16, bold: bool) -> Self {
+        Self { color, bold }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cell {
+    pub ch: char,
+    pub attr: Attr,
+}
+
+// in-memory copy of the screen, so a tree can be rendered without a terminal
+#[derive(Default, Clone)]
+pub struct Canvas {
+    pub width: i32,
+    pub height: i32,
+    cells: Vec<Option<Cell>>,
+}
+
+impl Canvas {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width: width.max(0),
+            height: height.max(0),
+            cells: vec![None; (width.max(0) * height.max(0)) as usize],
+        }
+    }
+
+    pub fn get(&self, y: i32, x: i32) -> Option<Cell> {
+        self.index(y, x).and_then(|i| self.cells[i])
+    }
+
+    pub fn set(&mut self, y: i32, x: i32, cell: Option<Cell>) {
+        if let Some(i) = self.index(y, x) {
+            self.cells[i] = cell;
+        }
+    }
+
+    // write a string starting at (y, x); characters falling off the canvas are dropped
+    pub fn put_str(&mut self, y: i32, x: i32, text: &str, attr: Attr) {
+        for (ch, x) in text.chars().zip(x..) {
+            self.set(y, x, Some(Cell { ch, attr }));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|cell| *cell = None);
+    }
+
+    // clear rows [0, rows)
+    pub fn clear_top(&mut self, rows: i32) {
+        let end = (rows.clamp(0, self.height) * self.width) as usize;
+        self.cells[..end].iter_mut().for_each(|cell| *cell = None);
+    }
+
+    // draw the filled cells of another canvas on top of this one, with its corner at (y, x)
+    pub fn paste(&mut self, y: i32, x: i32, top: &Canvas) {
+        for top_y in 0..top.height {
+            for top_x in 0..top.width {
+                if let Some(cell) = top.get(top_y, top_x) {
+                    self.set(y + top_y, x + top_x, Some(cell));
+                }
+            }
+        }
+    }
+
+    pub fn row(&self, y: i32) -> &[Option<Cell>] {
+        let start = (y * self.width) as usize;
+        &self.cells[start..start + self.width as usize]
+    }
+
+    fn index(&self, y: i32, x: i32) -> Option<usize> {
+        if (0..self.height).contains(&y) && (0..self.width).contains(&x) {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+}
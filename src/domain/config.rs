@@ -1,13 +1,34 @@
-use clap::{arg, command};
+use std::{
+    io::{self, IsTerminal},
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{bail, Context};
+use clap::{arg, command, Command};
 use rand::Rng;
 
+use super::{
+    garden::{self, Wilt},
+    history,
+    keymap::Keymap,
+    progress,
+    sky::Weather,
+    theme::{Hemisphere, Rgb, Theme, TreeColors},
+    transition::Transition,
+};
+
 #[allow(clippy::struct_excessive_bools)]
+#[derive(Clone)]
 pub struct Config {
+    pub mode: Mode,
     pub live: bool,
     pub infinite: bool,
     pub screensaver: bool,
     pub print_tree: bool,
     pub verbose: bool,
+    pub show_seed: bool,
     pub life_start: i32,
     pub multiplier: i32,
     pub base_type: BaseType,
@@ -16,25 +37,115 @@ pub struct Config {
     pub save: bool,
     pub load: bool,
     pub target_branch_count: i32,
+    pub cuts: Vec<usize>,
+    // life and multiplier a loaded tree grew with, used as they are instead of shrinking or
+    // fitting again
+    pub growth: Option<(i32, i32)>,
+    pub prune: bool,
+    // stop growing after this many branches, 0 grows the whole tree
+    pub branch_limit: i32,
+    pub wilt: Wilt,
+    // grow a smaller tree on a screen smaller than 80x24
+    pub shrink_to_fit: bool,
+    // fraction of the tree area --fit grows a tree to cover
+    pub fit: Option<f64>,
+    pub align: Align,
+    // columns right and rows up from where align plants the tree
+    pub offset: (i32, i32),
+    // keep a column beside the tree for the message instead of drawing it over the tree
+    pub message_column: bool,
+    // trees planted side by side, 0 or 1 for a single tree
+    pub forest: usize,
+    // a forest stands on a shared ground line instead of in pots
+    pub ground: bool,
+    // columns a second the panorama pans across its landscape
+    pub panorama: Option<f64>,
+    // grow past the screen edges, with a view to pan and zoom and outputs of the whole tree
+    pub scroll: bool,
+    pub garden_file: String,
+    // length of a focus session, growth is stretched to fill it
+    pub focus: Option<Duration>,
+    pub history_file: String,
+    // grow along with progress piped into stdin
+    pub progress_from_stdin: bool,
 
     pub time_wait: i32,
     pub time_step: u64,
+    // how a tree leaves in infinite mode, taking up to transition_time at the end of time_wait
+    pub transition: Transition,
+    pub transition_time: Duration,
+    // once grown, about this many leaves a second drop off the tree and pile up on its base
+    pub falling_leaves: Option<f64>,
+    // the sky drawn behind the tree
+    pub weather: Weather,
 
     pub message: String,
     pub leaves: String,
     pub save_file: String,
     pub load_file: String,
+
+    pub headless: bool,
+    // render the finished tree to stdout in format, no terminal ui
+    pub once: bool,
+    pub format: Format,
+    pub export_file: String,
+    pub record_file: String,
+    pub canvas_width: i32,
+    pub canvas_height: i32,
+    pub cell_width: u32,
+    pub cell_height: u32,
+    pub html_page: bool,
+    pub gif_batch: u32,
+    pub gif_hold: f64,
+    pub theme: Theme,
+    // with --theme auto, theme.tree follows the local clock and calendar of this hemisphere
+    pub auto_theme: bool,
+    pub hemisphere: Hemisphere,
+    pub keymap: Keymap,
 }
 
 impl Config {
     pub fn from_args() -> Self {
         let matches = get_arg_matches();
-        match parse_arg_matches(&matches) {
+        let mut config = match parse_arg_matches(&matches) {
             Ok(ok) => ok,
+            // a run nobody watches would quietly render the wrong tree, into its output too
+            Err(why) if unattended(&matches) => {
+                eprintln!("couldnt read args {why}");
+                std::process::exit(2);
+            }
             Err(why) => {
                 println!("couldnt read args {why} \n defaulting to defaults");
                 Self::default()
             }
+        };
+        config.check_once(matches.get_flag("once"));
+        config.refresh_theme();
+        config
+    }
+
+    // with --theme auto, color trees grown from now on for the time of day and season it is
+    pub fn refresh_theme(&mut self) {
+        if self.auto_theme {
+            self.theme.tree = TreeColors::now(self.hemisphere);
+        }
+    }
+
+    // piped into a file, cron or a ci log there is nobody to watch growth or press keys, so
+    // the tree is rendered once. exports and recordings already write files, focus and
+    // progress sessions use /dev/tty
+    fn check_once(&mut self, asked: bool) {
+        let piped = !io::stdout().is_terminal()
+            && matches!(self.mode, Mode::Tree | Mode::Garden)
+            && self.export_file.is_empty()
+            && self.record_file.is_empty()
+            && self.focus.is_none()
+            && !self.progress_from_stdin
+            && !self.prune
+            && self.panorama.is_none();
+        if asked || piped {
+            self.once = true;
+            self.headless = true;
         }
     }
 }
@@ -42,11 +153,13 @@ impl Config {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            mode: Mode::Tree,
             live: false,
             infinite: false,
             screensaver: false,
             print_tree: false,
             verbose: false,
+            show_seed: false,
             life_start: 64,
             multiplier: 10,
             base_type: BaseType::Big,
@@ -55,29 +168,134 @@ impl Default for Config {
             save: false,
             load: false,
             target_branch_count: 0,
+            cuts: Vec::new(),
+            growth: None,
+            prune: false,
+            branch_limit: 0,
+            wilt: Wilt::Healthy,
+            shrink_to_fit: true,
+            fit: None,
+            align: Align::Center,
+            offset: (0, 0),
+            message_column: false,
+            forest: 0,
+            ground: false,
+            panorama: None,
+            scroll: false,
+            garden_file: garden::default_file(),
+            focus: None,
+            history_file: history::default_file(),
+            progress_from_stdin: false,
             time_wait: 4,
+            transition: Transition::Cut,
+            transition_time: Duration::from_secs(2),
+            falling_leaves: None,
+            weather: Weather::None,
             time_step: 30,
             message: String::default(),
             leaves: ['&'; 64].iter().collect(),
-            save_file: String::default(),
-            load_file: String::default(),
+            save_file: progress::default_file(),
+            load_file: progress::default_file(),
+            headless: false,
+            once: false,
+            format: Format::Ansi,
+            export_file: String::default(),
+            record_file: String::default(),
+            canvas_width: 80,
+            canvas_height: 24,
+            cell_width: 12,
+            cell_height: 18,
+            html_page: false,
+            gif_batch: 5,
+            gif_hold: 3.0,
+            theme: Theme::default(),
+            auto_theme: false,
+            hemisphere: Hemisphere::North,
+            keymap: Keymap::default(),
         }
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Mode {
+    Tree,
+    // a tree kept in the cache dir that grows a little every day
+    Garden,
+    Water,
+    // the forest of past focus sessions
+    History,
+}
+
+// how a tree printed to stdout is written
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    Ansi,
+    Plain,
+    Html,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Ok(match value {
+            "ansi" => Self::Ansi,
+            "plain" | "text" => Self::Plain,
+            "html" => Self::Html,
+            "json" => Self::Json,
+            _ => bail!("unknown format {value:?}, use ansi, plain, html or json"),
+        })
+    }
+}
+
+// which side of the screen the tree is planted on
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl FromStr for Align {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Ok(match value {
+            "left" => Self::Left,
+            "center" | "centre" => Self::Center,
+            "right" => Self::Right,
+            _ => bail!("unknown alignment {value:?}, use left, center or right"),
+        })
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
 pub enum BaseType {
+    #[default]
     None,
     Small,
     Big,
 }
 
 #[allow(clippy::cognitive_complexity)]
+// rendering once, exporting or piped, as check_once would find before the args are read
+fn unattended(matches: &clap::ArgMatches) -> bool {
+    matches.get_flag("once")
+        || matches.get_one::<String>("export").is_some()
+        || !io::stdout().is_terminal()
+}
+
 fn get_arg_matches() -> clap::ArgMatches {
     command!()
         .arg(arg!(-l --live "live mode: show each step of growth"))
         .arg(arg!(-t --time <TIME> "in live mode, wait TIME secs between steps of growth (must be larger than 0) [default: 0.03]"))
         .arg(arg!(-i --infinite "infinite mode: keep growing trees"))
         .arg(arg!(-w --wait <TIME> "in infinite mode, wait TIME between each tree generation [default: 4.00]"))
+        .arg(arg!(--transition <KIND> "in infinite mode, how a tree leaves before the next: cut, fall, dissolve, wipe or wither [default: cut]"))
+        .arg(arg!(--"transition-time" <SECS> "how long a transition takes, out of the wait between trees [default: 2]"))
+        .arg(arg!(--weather <KIND> "a sky behind the tree: day, night, clouds, rain, snow, or random for a new one every tree [default: none]").conflicts_with("prune"))
+        .arg(arg!(--"falling-leaves" [RATE] "once grown, leaves drop off the tree, sway down and pile up on the base, then slowly grow back; about RATE leaves a second [default: 0.5]").num_args(0..=1).default_missing_value("0.5"))
         .arg(arg!(-S --screensaver "screensaver mode; equivalent to -li and quit on any keypress"))
         .arg(arg!(-m --message <STR> "attach message next to the tree"))
         .arg(arg!(-b --base <INT> "ascii-art plant base to use, 0 is none"))
@@ -89,6 +307,34 @@ fn get_arg_matches() -> clap::ArgMatches {
         .arg(arg!(-W --save <FILE> "save progress to file [default: $XDG_CACHE_HOME/cbonsai or $HOME/.cache/cbonsai]"))
         .arg(arg!(-C --load <FILE> "load progress from file [default: $XDG_CACHE_HOME/cbonsai]"))
         .arg(arg!(-v --verbose "increase output verbosity"))
+        .arg(arg!(--"show-seed" "keep the current seed on screen"))
+        .arg(arg!(-k --keys <LIST> "comma-delimited ACTION=KEY overrides, e.g. pause=p,new=space; actions: quit, pause, faster, slower, new, regrow, save, help, up, down, left, right, zoom-out, zoom-in, cut, undo, redo"))
+        .arg(arg!(-P --prune "prune the finished tree: pick branches with the arrow keys and cut them off"))
+        .arg(arg!(-e --export <FILE> "render the finished tree to FILE without a terminal (after pruning with --prune); format from extension (png, html, gif, json)"))
+        .arg(arg!(-r --record <FILE> "record growth as an asciicast v2 file; without --live no terminal is used"))
+        .arg(arg!(--size <COLSxROWS> "canvas size used when rendering without a terminal [default: 80x24]"))
+        .arg(arg!(--"cell-size" <WxH> "pixel size of one character cell in image exports [default: 12x18]"))
+        .arg(arg!(--"html-page" "with an html export, write a standalone page instead of a snippet"))
+        .arg(arg!(--"gif-batch" <STEPS> "steps of growth per frame of a gif export [default: 5]"))
+        .arg(arg!(--"gif-hold" <SECS> "how long a gif export holds the finished tree [default: 3]"))
+        .arg(arg!(--theme <NAME> "colors of the tree: default, spring, summer, autumn, winter, or auto for the season and time of day, darker at night [default: default]"))
+        .arg(arg!(--hemisphere <SIDE> "north or south, which seasons --theme auto follows [default: north]"))
+        .arg(arg!(--background <COLOR> "background color of image and html page exports, as #rrggbb [default: #000000]"))
+        .arg(arg!(-f --focus <DURATION> "focus timer: grow one tree live over DURATION (e.g. 25m, 90s, 1h) with a countdown; quitting early withers it"))
+        .arg(arg!(--"progress-from-stdin" "grow along with progress piped in, read from percentages or n/m counters; errors kill the tree"))
+        .arg(arg!(--fit [FILL] "size the tree to cover about FILL of the screen (0-1) without running off it, picking life, multiplier and seed rerolls [default: 0.5]").num_args(0..=1).default_missing_value("0.5"))
+        .arg(arg!(--align <SIDE> "plant the tree on the left, center or right of the screen [default: center]"))
+        .arg(arg!(--offset <"X,Y"> "move the tree, its base and message X columns right and Y rows up; negative X moves left").allow_hyphen_values(true))
+        .arg(arg!(--"message-column" "keep a column beside the tree for the message instead of drawing it over the tree"))
+        .arg(arg!(--forest <N> "plant N trees of mixed species and sizes side by side, growing together").conflicts_with_all(["prune", "focus", "progress-from-stdin", "save", "load", "scroll"]))
+        .arg(arg!(--ground "in a forest, plant the trees in a shared ground line instead of pots"))
+        .arg(arg!(--panorama [SPEED] "screensaver panning across an endless landscape of trees grown off screen, SPEED columns a second [default: 4]").num_args(0..=1).default_missing_value("4"))
+        .arg(arg!(--scroll "let the tree grow past the screen: pan with the arrow keys, zoom out with z and back in with Z; exports and printed trees show all of it"))
+        .arg(arg!(--once "render the tree to stdout once and exit, without a terminal ui; the default when stdout is not a terminal"))
+        .arg(arg!(--format <FORMAT> "format of a tree rendered to stdout: ansi, plain, html or json [default: ansi]"))
+        .subcommand(Command::new("garden").about("tend a bonsai that keeps growing over real days"))
+        .subcommand(Command::new("water").about("water the garden bonsai so it does not wilt"))
+        .subcommand(Command::new("history").about("show past focus sessions as a forest"))
         .get_matches()
 }
 
@@ -110,14 +356,35 @@ fn parse_arg_matches(matches: &clap::ArgMatches) -> anyhow::Result<Config> {
         config.time_wait = value.parse()?;
     }
 
-    if let Some(value) = matches.get_one::<bool>("screensaver") {
-        config.live = *value;
-        config.infinite = *value;
+    if let Some(value) = matches.get_one::<String>("transition") {
+        config.transition = value.parse()?;
+    }
 
-        config.save = *value;
-        config.load = *value;
+    if let Some(value) = matches.get_one::<String>("transition-time") {
+        config.transition_time = Duration::try_from_secs_f64(value.parse()?)?;
+    }
 
-        config.screensaver = *value;
+    if let Some(value) = matches.get_one::<String>("weather") {
+        config.weather = value.parse()?;
+    }
+
+    if let Some(value) = matches.get_one::<String>("falling-leaves") {
+        let rate: f64 = value.parse()?;
+        if rate.is_nan() || rate <= 0.0 {
+            bail!("--falling-leaves takes a number of leaves a second above 0, got {value}");
+        }
+        config.falling_leaves = Some(rate);
+    }
+
+    // flags are always present, only let screensaver override -l and -i when it is set
+    if let Some(true) = matches.get_one::<bool>("screensaver") {
+        config.live = true;
+        config.infinite = true;
+
+        config.save = true;
+        config.load = true;
+
+        config.screensaver = true;
     }
 
     if let Some(value) = matches.get_one::<String>("message") {
@@ -166,5 +433,258 @@ fn parse_arg_matches(matches: &clap::ArgMatches) -> anyhow::Result<Config> {
         config.verbose = *value;
     }
 
+    if let Some(value) = matches.get_one::<bool>("show-seed") {
+        config.show_seed = *value;
+    }
+
+    if let Some(value) = matches.get_one::<String>("keys") {
+        config.keymap.parse_overrides(value)?;
+    }
+
+    if let Some(value) = matches.get_one::<bool>("prune") {
+        config.prune = *value;
+    }
+
+    if let Some(value) = matches.get_one::<String>("export") {
+        // a pruned tree is exported once the pruning session ends
+        config.headless = !config.prune;
+        config.export_file = value.clone();
+    }
+
+    if let Some(value) = matches.get_one::<String>("record") {
+        // a live recording captures the real session, otherwise growth is recorded off screen
+        config.headless |= !config.live;
+        config.record_file = value.clone();
+    }
+
+    if let Some(value) = matches.get_one::<String>("size") {
+        (config.canvas_width, config.canvas_height) = parse_dimensions(value)?;
+    }
+
+    if let Some(value) = matches.get_one::<String>("cell-size") {
+        let (width, height) = parse_dimensions(value)?;
+        config.cell_width = width.try_into()?;
+        config.cell_height = height.try_into()?;
+    }
+
+    if let Some(value) = matches.get_one::<bool>("html-page") {
+        config.html_page = *value;
+    }
+
+    if let Some(value) = matches.get_one::<String>("gif-batch") {
+        config.gif_batch = value.parse()?;
+    }
+
+    if let Some(value) = matches.get_one::<String>("gif-hold") {
+        config.gif_hold = value.parse()?;
+    }
+
+    if let Some(value) = matches.get_one::<String>("theme") {
+        match value.as_str() {
+            "default" => {}
+            "auto" => config.auto_theme = true,
+            season => {
+                let season = season.parse().with_context(|| {
+                    format!("unknown theme {value:?}, use default, auto, spring, summer, autumn or winter")
+                })?;
+                config.theme.tree = TreeColors::seasonal(season, false);
+            }
+        }
+    }
+
+    if let Some(value) = matches.get_one::<String>("hemisphere") {
+        config.hemisphere = value.parse()?;
+    }
+
+    if let Some(value) = matches.get_one::<String>("background") {
+        config.theme.background = value.parse::<Rgb>()?;
+    }
+
+    if let Some(value) = matches.get_one::<String>("focus") {
+        let focus = parse_duration(value)?;
+        if focus.is_zero() {
+            bail!("a focus session cant be empty");
+        }
+        config.focus = Some(focus);
+        // one tree grown over the whole session
+        config.live = true;
+        config.infinite = false;
+    }
+
+    if let Some(true) = matches.get_one::<bool>("progress-from-stdin") {
+        config.progress_from_stdin = true;
+        config.live = true;
+        config.infinite = false;
+    }
+
+    match matches.subcommand_name() {
+        Some("garden") => {
+            config.mode = Mode::Garden;
+            // garden growth since the last visit is shown live
+            config.live = true;
+        }
+        // watering only touches the garden file, no terminal needed
+        Some("water") => {
+            config.mode = Mode::Water;
+            config.headless = true;
+        }
+        // the forest is printed, no terminal ui
+        Some("history") => {
+            config.mode = Mode::History;
+            config.headless = true;
+        }
+        _ => {}
+    }
+
+    if let Some(value) = matches.get_one::<String>("fit") {
+        let fill: f64 = value.parse()?;
+        if !(fill > 0.0 && fill <= 1.0) {
+            bail!("--fit takes a fraction of the screen between 0 and 1, got {value}");
+        }
+        config.fit = Some(fill);
+    }
+
+    if let Some(value) = matches.get_one::<String>("align") {
+        config.align = value.parse()?;
+    }
+
+    if let Some(value) = matches.get_one::<String>("offset") {
+        let (x, y) = value
+            .split_once(',')
+            .with_context(|| format!("expected X,Y, got {value:?}"))?;
+        config.offset = (x.trim().parse()?, y.trim().parse()?);
+    }
+
+    if let Some(value) = matches.get_one::<bool>("message-column") {
+        config.message_column = *value;
+    }
+
+    if let Some(value) = matches.get_one::<String>("forest") {
+        config.forest = value.parse()?;
+    }
+
+    if let Some(value) = matches.get_one::<bool>("ground") {
+        config.ground = *value;
+    }
+
+    if let Some(value) = matches.get_one::<String>("panorama") {
+        let speed: f64 = value.parse()?;
+        if speed.is_nan() || speed <= 0.0 {
+            bail!("the panorama has to move, got a speed of {value}");
+        }
+        config.panorama = Some(speed);
+        config.time_step = (1000.0 / speed) as u64;
+        config.live = true;
+        config.infinite = true;
+        // there is no one tree to save
+        config.save = false;
+        config.load = false;
+    }
+
+    if let Some(true) = matches.get_one::<bool>("scroll") {
+        config.scroll = true;
+        // the screen no longer limits how big the tree gets
+        config.shrink_to_fit = false;
+    }
+
+    // the garden and the history keep to one tree each, without a sky
+    if config.mode != Mode::Tree {
+        config.forest = 0;
+        config.weather = Weather::None;
+    }
+
+    if let Some(value) = matches.get_one::<String>("format") {
+        config.format = value.parse()?;
+    }
+
+    // the trees of a forest are grown side by side, json and save files hold a single tree
+    if config.forest > 1 {
+        let json_export = Path::new(&config.export_file).extension() == Some("json".as_ref());
+        if config.format == Format::Json || json_export {
+            bail!("a forest cant be written as json, it holds a single tree");
+        }
+        config.save = false;
+        config.load = false;
+    }
+
     Ok(config)
 }
+
+// parse "WIDTHxHEIGHT", e.g. "80x24"
+fn parse_dimensions(value: &str) -> anyhow::Result<(i32, i32)> {
+    let (width, height) = value
+        .split_once('x')
+        .with_context(|| format!("expected WIDTHxHEIGHT, got {value:?}"))?;
+    let (width, height): (i32, i32) = (width.parse()?, height.parse()?);
+    if width <= 0 || height <= 0 {
+        bail!("dimensions must be positive, got {value:?}");
+    }
+    Ok((width, height))
+}
+
+// longest duration accepted, so a deadline that far out is still a valid instant
+const LONGEST_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+// parse a duration like "25m", "90s" or "1h"; a bare number is minutes
+fn parse_duration(value: &str) -> anyhow::Result<Duration> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "m"),
+    };
+    let number: u64 = number
+        .parse()
+        .with_context(|| format!("expected a duration like 25m, got {value:?}"))?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => bail!("unknown unit in {value:?}, use s, m or h"),
+    };
+    match number.checked_mul(unit).map(Duration::from_secs) {
+        Some(duration) if duration <= LONGEST_DURATION => Ok(duration),
+        _ => bail!("{value:?} is too long, durations go up to 24h"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_durations() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("25m").unwrap(), Duration::from_secs(25 * 60));
+        assert_eq!(
+            parse_duration("2h").unwrap(),
+            Duration::from_secs(2 * 60 * 60)
+        );
+        // a bare number is minutes
+        assert_eq!(parse_duration("5").unwrap(), Duration::from_secs(5 * 60));
+        assert_eq!(parse_duration("0s").unwrap(), Duration::ZERO);
+        assert_eq!(parse_duration("24h").unwrap(), LONGEST_DURATION);
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for value in [
+            "",
+            "m",
+            "-5m",
+            "1.5h",
+            "10d",
+            "5 m",
+            "5mm",
+            "m5",
+            "h1",
+            "99999999999999999999s",
+            // overflows once turned into seconds
+            "9999999999999999h",
+            "18446744073709551615m",
+            // longer than a day
+            "25h",
+            "86401s",
+        ] {
+            assert!(parse_duration(value).is_err(), "{value:?}");
+        }
+    }
+}
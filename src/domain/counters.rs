@@ -1,6 +0,0 @@
-#[derive(Default)]
-pub struct Counters {
-    pub branches: i32,
-    pub shoots: i32,
-    pub shoot_counter: i32,
-}
@@ -0,0 +1,247 @@
+use std::{f64::consts::TAU, time::Duration};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::canvas::{Canvas, Cell};
+
+// time between idle frames, slow enough to leave the cpu alone
+pub const TICK: Duration = Duration::from_millis(150);
+// rows a leaf falls in a tick
+const FALL: f64 = 0.4;
+// columns a leaf drifts either way as it sways
+const SWAY: f64 = 1.5;
+const PILE_HEIGHT: i32 = 2;
+// ticks before a leaf grows back where one fell from
+const REGROW: (u32, u32) = (200, 600);
+
+struct Leaf {
+    y: f64,
+    x: f64,
+    phase: f64,
+    cell: Cell,
+}
+
+impl Leaf {
+    // where the leaf is drawn, swaying around the column it fell from
+    fn at(&self) -> (i32, i32) {
+        (
+            self.y.round() as i32,
+            (self.x + self.phase.sin() * SWAY).round() as i32,
+        )
+    }
+}
+
+// a finished tree idly dropping its leaves, which sway down and pile up on the base while
+// new ones slowly grow back
+pub struct FallingLeaves {
+    rng: StdRng,
+    // chance of a leaf letting go in a tick, more than 1 lets go of several
+    chance: f64,
+    tree: Canvas,
+    // leaves still on the tree, as (y, x)
+    hanging: Vec<(i32, i32)>,
+    // spots leaves fell from, with the tick they grow back on
+    bare: Vec<(i32, i32, u32)>,
+    falling: Vec<Leaf>,
+    pile: Canvas,
+    // landed leaves, the last one on top of its column
+    piled: Vec<(i32, i32)>,
+    // row leaves land on in each column, none where they fall out of sight
+    floor: Vec<Option<i32>>,
+    ticks: u32,
+}
+
+impl FallingLeaves {
+    // tree is the grown tree and leaves the cells of it that are leaves, per_second is about
+    // how many let go a second
+    pub fn new(
+        tree: Canvas,
+        leaves: &Canvas,
+        floor: Vec<Option<i32>>,
+        seed: u64,
+        per_second: f64,
+    ) -> Self {
+        let hanging = (0..tree.height)
+            .flat_map(|y| (0..tree.width).map(move |x| (y, x)))
+            .filter(|&(y, x)| tree.get(y, x).is_some() && leaves.get(y, x).is_some())
+            .collect();
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            chance: per_second * TICK.as_secs_f64(),
+            pile: Canvas::new(tree.width, tree.height),
+            tree,
+            hanging,
+            bare: Vec::new(),
+            falling: Vec::new(),
+            piled: Vec::new(),
+            floor,
+            ticks: 0,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+
+        let mut chance = self.chance;
+        while chance > 0.0 && !self.hanging.is_empty() {
+            if self.rng.gen::<f64>() < chance {
+                let (y, x) = self
+                    .hanging
+                    .swap_remove(self.rng.gen_range(0..self.hanging.len()));
+                if let Some(cell) = self.tree.get(y, x) {
+                    let phase = self.rng.gen_range(0.0..TAU);
+                    let (x, y) = (f64::from(x) - phase.sin() * SWAY, f64::from(y));
+                    self.falling.push(Leaf { y, x, phase, cell });
+                }
+                let back = self.ticks + self.rng.gen_range(REGROW.0..REGROW.1);
+                self.bare.push((y, x, back));
+            }
+            chance -= 1.0;
+        }
+
+        // a leaf grown back makes up for one rotting away on the pile
+        let ticks = self.ticks;
+        let (grown, bare) = self.bare.iter().partition(|&&(.., back)| back <= ticks);
+        self.bare = bare;
+        for (y, x, _) in grown {
+            self.hanging.push((y, x));
+            if let Some((y, x)) = self.piled.pop() {
+                self.pile.set(y, x, None);
+            }
+        }
+
+        let (tree, pile, piled, floor) = (&self.tree, &mut self.pile, &mut self.piled, &self.floor);
+        let bottom = tree.height;
+        self.falling.retain_mut(|leaf| {
+            leaf.y += FALL;
+            leaf.phase += 0.3;
+            let (y, x) = leaf.at();
+            let Some(&Some(floor)) = usize::try_from(x).ok().and_then(|x| floor.get(x)) else {
+                return y < bottom;
+            };
+            // lands on the highest leaf of the column, around the trunk, or goes once the
+            // pile is full
+            let Some(top) = (floor - PILE_HEIGHT + 1..=floor)
+                .rev()
+                .find(|&row| pile.get(row, x).is_none() && tree.get(row, x).is_none())
+            else {
+                return y < floor - PILE_HEIGHT;
+            };
+            if y < top {
+                return true;
+            }
+            pile.set(top, x, Some(leaf.cell));
+            piled.push((top, x));
+            false
+        });
+    }
+
+    // the tree as it is now, with its bare spots, falling leaves and the pile
+    pub fn frame(&self) -> Canvas {
+        let mut frame = self.tree.clone();
+        for &(y, x, _) in &self.bare {
+            frame.set(y, x, None);
+        }
+        frame.paste(0, 0, &self.pile);
+        for leaf in &self.falling {
+            let (y, x) = leaf.at();
+            frame.set(y, x, Some(leaf.cell));
+        }
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::canvas::Attr;
+
+    const LEAF: Attr = Attr::new(2, false);
+    const WOOD: Attr = Attr::new(3, true);
+
+    // a trunk in column 10 under a crown of leaves, on a 20x12 screen
+    fn idle(floor: Option<i32>, per_second: f64, seed: u64) -> FallingLeaves {
+        let mut tree = Canvas::new(20, 12);
+        let mut leaves = Canvas::new(20, 12);
+        for y in 3..12 {
+            tree.put_str(y, 10, "|", WOOD);
+        }
+        for y in 0..3 {
+            tree.put_str(y, 6, "&&&&&&&&&", LEAF);
+            leaves.put_str(y, 6, "&&&&&&&&&", LEAF);
+        }
+        FallingLeaves::new(tree, &leaves, vec![floor; 20], seed, per_second)
+    }
+
+    fn count(canvas: &Canvas, ch: char) -> usize {
+        (0..canvas.height)
+            .flat_map(|y| canvas.row(y).iter())
+            .filter(|cell| cell.is_some_and(|cell| cell.ch == ch))
+            .count()
+    }
+
+    #[test]
+    fn drops_only_leaves() {
+        let mut leaves = idle(Some(9), 4.0, 7);
+        assert_eq!(leaves.hanging.len(), 27);
+        for _ in 0..300 {
+            leaves.tick();
+            assert!(leaves.hanging.iter().all(|&(y, _)| y < 3));
+            assert!(leaves.bare.iter().all(|&(y, ..)| y < 3));
+            // falling leaves pass in front of the trunk, it is never bare
+            let frame = leaves.frame();
+            assert!((3..12).all(|y| frame.get(y, 10).is_some()));
+            assert!(count(&frame, '&') <= 27);
+        }
+    }
+
+    #[test]
+    fn piles_leaves_on_the_floor() {
+        let mut leaves = idle(Some(9), 4.0, 7);
+        for _ in 0..150 {
+            leaves.tick();
+        }
+        assert!(!leaves.piled.is_empty());
+        for &(y, x) in &leaves.piled {
+            assert!((9 - PILE_HEIGHT + 1..=9).contains(&y));
+            assert_eq!(leaves.frame().get(y, x).map(|cell| cell.ch), Some('&'));
+        }
+    }
+
+    #[test]
+    fn lets_leaves_fall_out_of_sight_without_a_floor() {
+        let mut leaves = idle(None, 4.0, 7);
+        for _ in 0..150 {
+            leaves.tick();
+            assert!(leaves.falling.iter().all(|leaf| leaf.at().0 < 12));
+        }
+        assert!(leaves.piled.is_empty());
+    }
+
+    #[test]
+    fn grows_leaves_back() {
+        let mut leaves = idle(Some(9), 0.5, 7);
+        while leaves.bare.is_empty() {
+            leaves.tick();
+        }
+        let (y, x, back) = leaves.bare[0];
+        assert!(!leaves.hanging.contains(&(y, x)));
+        while leaves.ticks < back {
+            leaves.tick();
+        }
+        assert!(leaves.hanging.contains(&(y, x)));
+    }
+
+    #[test]
+    fn falls_the_same_way_from_a_seed() {
+        let (mut first, mut again) = (idle(Some(9), 2.0, 7), idle(Some(9), 2.0, 7));
+        for _ in 0..100 {
+            first.tick();
+            again.tick();
+        }
+        let (first, again) = (first.frame(), again.frame());
+        for y in 0..12 {
+            assert_eq!(first.row(y), again.row(y));
+        }
+    }
+}
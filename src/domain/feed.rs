@@ -0,0 +1,197 @@
+use std::{
+    io::{BufRead, ErrorKind},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+// markers of a failed build or test run
+const FAILURE_MARKERS: [&str; 4] = ["error:", "error[", "FAILED", "*** "];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Report {
+    // share of the work done, 0 to 1
+    Progress(f64),
+    Failed,
+    // the input ended, the work is done
+    End,
+}
+
+// progress read from lines of piped input on a background thread
+pub struct Feed {
+    receiver: Receiver<Report>,
+    pub fraction: f64,
+    pub failed: bool,
+    pub ended: bool,
+}
+
+impl Feed {
+    pub fn spawn(input: impl BufRead + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut input = input;
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                // build output is not always utf-8, only the end of input ends the work
+                match input.read_until(b'\n', &mut line) {
+                    Ok(0) => break,
+                    Ok(_) => {}
+                    Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                    // the work may still be going, just no longer readable
+                    Err(_) => return,
+                }
+                if let Some(report) = parse_line(&String::from_utf8_lossy(&line)) {
+                    if sender.send(report).is_err() {
+                        return;
+                    }
+                }
+            }
+            let _ = sender.send(Report::End);
+        });
+        Self {
+            receiver,
+            fraction: 0.0,
+            failed: false,
+            ended: false,
+        }
+    }
+
+    // apply the reports that arrived, waiting up to timeout for one. returns whether
+    // anything changed
+    pub fn update(&mut self, timeout: Duration) -> bool {
+        let mut report = match self.receiver.recv_timeout(timeout) {
+            Ok(report) => report,
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return false,
+        };
+        loop {
+            match report {
+                // progress only moves forward, a new stage starting at 0% keeps the tree
+                Report::Progress(fraction) => self.fraction = self.fraction.max(fraction),
+                Report::Failed => self.failed = true,
+                Report::End => {
+                    self.ended = true;
+                    self.fraction = 1.0;
+                }
+            }
+            match self.receiver.try_recv() {
+                Ok(next) => report = next,
+                Err(_) => return true,
+            }
+        }
+    }
+}
+
+// a percentage like "[ 45%]" or a counter like "[3/10]" in a line, or a failure marker
+pub fn parse_line(line: &str) -> Option<Report> {
+    if FAILURE_MARKERS.iter().any(|marker| line.contains(marker)) {
+        return Some(Report::Failed);
+    }
+    percentage(line)
+        .or_else(|| counter(line))
+        .map(|fraction| Report::Progress(fraction.clamp(0.0, 1.0)))
+}
+
+fn percentage(line: &str) -> Option<f64> {
+    line.match_indices('%').find_map(|(i, _)| {
+        let number = trailing_number(&line[..i]);
+        number.parse::<f64>().ok().map(|percent| percent / 100.0)
+    })
+}
+
+fn counter(line: &str) -> Option<f64> {
+    line.match_indices('/').find_map(|(i, _)| {
+        let done: u64 = trailing_number(&line[..i]).parse().ok()?;
+        let after = &line[i + 1..];
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let total: u64 = after[..digits].parse().ok()?;
+        (total > 0 && done <= total).then(|| done as f64 / total as f64)
+    })
+}
+
+// the number text ends with, e.g. "45" in "[ 45"
+fn trailing_number(text: &str) -> &str {
+    let start = text
+        .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+        .len();
+    &text[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufReader, Cursor, Read};
+
+    use super::*;
+
+    // input that fails once read, after whatever it is chained to
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("broken pipe"))
+        }
+    }
+
+    fn settle(feed: &mut Feed) {
+        while feed.update(Duration::from_millis(100)) {}
+    }
+
+    #[test]
+    fn reads_past_lines_that_are_not_utf8() {
+        let input = b"[ 10%] start\n\xff\xfe garbage\n[ 50%] caf\xe9\n".as_slice();
+        let mut feed = Feed::spawn(BufReader::new(input.chain(Broken)));
+        settle(&mut feed);
+        assert!((feed.fraction - 0.5).abs() < 1e-9);
+        assert!(!feed.ended);
+        assert!(!feed.failed);
+    }
+
+    #[test]
+    fn ends_at_the_end_of_input() {
+        let mut feed = Feed::spawn(Cursor::new(b"[1/4]\n\xff\n".to_vec()));
+        settle(&mut feed);
+        assert!(feed.ended);
+        assert!((feed.fraction - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reads_percentages_and_counters() {
+        assert_eq!(parse_line("[ 45%] Building"), Some(Report::Progress(0.45)));
+        assert_eq!(parse_line("12.5% done"), Some(Report::Progress(0.125)));
+        assert_eq!(parse_line("[3/10] cc main.c"), Some(Report::Progress(0.3)));
+        assert_eq!(parse_line("Compiling (7/7)"), Some(Report::Progress(1.0)));
+        // past 100% still only fills the tree
+        assert_eq!(parse_line("250%"), Some(Report::Progress(1.0)));
+    }
+
+    #[test]
+    fn reads_failures() {
+        assert_eq!(parse_line("error: could not compile"), Some(Report::Failed));
+        assert_eq!(
+            parse_line("error[E0308]: mismatched types"),
+            Some(Report::Failed)
+        );
+        assert_eq!(parse_line("test parse ... FAILED"), Some(Report::Failed));
+        assert_eq!(parse_line("make: *** [all] Error 2"), Some(Report::Failed));
+        // a failure wins over the progress on the same line
+        assert_eq!(parse_line("[ 50%] error: oops"), Some(Report::Failed));
+    }
+
+    #[test]
+    fn rejects_lines_without_progress() {
+        for line in [
+            "",
+            "Compiling rustbonsai",
+            "%",
+            "[ %]",
+            "1.2.3% of something",
+            "and/or",
+            "3/",
+            "/10",
+            "3/0",
+            "11/10",
+        ] {
+            assert_eq!(parse_line(line), None, "{line:?}");
+        }
+    }
+}
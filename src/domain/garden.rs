@@ -0,0 +1,210 @@
+use std::{
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context};
+
+use super::progress;
+
+const DAY: u64 = 24 * 60 * 60;
+// a tree left without water starts yellowing, then wilts and stops growing
+const THIRSTY_AFTER: u64 = 3 * DAY;
+const WILTED_AFTER: u64 = 7 * DAY;
+// branches a freshly planted tree starts out with, and grows each day it has water
+const SEEDLING: f64 = 8.0;
+const BRANCHES_PER_DAY: f64 = 40.0;
+// (dying, dead) leaf colors of a tree going without water, yellow and then brown and gray
+pub const THIRSTY_LEAVES: (i16, i16) = (3, 11);
+pub const WILTED_LEAVES: (i16, i16) = (8, 3);
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Wilt {
+    #[default]
+    Healthy,
+    Thirsty,
+    Wilted,
+}
+
+impl Wilt {
+    // (dying, dead) leaf colors of a tree going without water, none for a healthy tree,
+    // which keeps the colors of its theme
+    pub fn leaf_colors(self) -> Option<(i16, i16)> {
+        match self {
+            Self::Healthy => None,
+            Self::Thirsty => Some(THIRSTY_LEAVES),
+            Self::Wilted => Some(WILTED_LEAVES),
+        }
+    }
+}
+
+// a tree that keeps growing between runs, timestamps are unix seconds
+pub struct Garden {
+    pub seed: u64,
+    pub planted: u64,
+    pub watered: u64,
+    pub visited: u64,
+    // branches grown so far, fractional so frequent visits still add up
+    pub growth: f64,
+    // branches shown the last time the garden was opened
+    pub seen: i32,
+}
+
+impl Garden {
+    pub fn plant(seed: u64, now: u64) -> Self {
+        Self {
+            seed,
+            planted: now,
+            watered: now,
+            visited: now,
+            growth: SEEDLING,
+            seen: 0,
+        }
+    }
+
+    // None when nothing has been planted yet
+    pub fn load(path: &str) -> anyhow::Result<Option<Self>> {
+        if !Path::new(path).exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(path).with_context(|| format!("couldnt load {path}"))?;
+        let mut garden = Self::plant(0, 0);
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+            match key {
+                "seed" => garden.seed = value.parse()?,
+                "planted" => garden.planted = value.parse()?,
+                "watered" => garden.watered = value.parse()?,
+                "visited" => garden.visited = value.parse()?,
+                "growth" => garden.growth = value.parse()?,
+                "seen" => garden.seen = value.parse()?,
+                _ => bail!("{path} is not a garden, unknown field {key:?}"),
+            }
+        }
+        Ok(Some(garden))
+    }
+
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = format!(
+            "seed {}\nplanted {}\nwatered {}\nvisited {}\ngrowth {}\nseen {}\n",
+            self.seed, self.planted, self.watered, self.visited, self.growth, self.seen
+        );
+        fs::write(path, text).with_context(|| format!("couldnt save to {path}"))
+    }
+
+    // grow for the time since the last visit, but only while the tree was not wilted
+    pub fn visit(&mut self, now: u64) {
+        let growing_until = now.min(self.watered + WILTED_AFTER);
+        let growing = growing_until.saturating_sub(self.visited);
+        self.growth += growing as f64 / DAY as f64 * BRANCHES_PER_DAY;
+        self.visited = now;
+    }
+
+    pub fn water(&mut self, now: u64) {
+        // bank the growth from before the tree wilted, so the time after it does not count
+        self.visit(now);
+        self.watered = now;
+    }
+
+    pub fn wilt(&self, now: u64) -> Wilt {
+        match now.saturating_sub(self.watered) {
+            neglect if neglect >= WILTED_AFTER => Wilt::Wilted,
+            neglect if neglect >= THIRSTY_AFTER => Wilt::Thirsty,
+            _ => Wilt::Healthy,
+        }
+    }
+
+    pub fn branches(&self) -> i32 {
+        self.growth as i32
+    }
+}
+
+// $XDG_CACHE_HOME/rustbonsai-garden, next to the cbonsai progress file
+pub fn default_file() -> String {
+    format!("{}/rustbonsai-garden", progress::cache_dir())
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+// "today", "yesterday" or "N days ago"
+pub fn days_ago(then: u64, now: u64) -> String {
+    match now.saturating_sub(then) / DAY {
+        0 => "today".to_owned(),
+        1 => "yesterday".to_owned(),
+        days => format!("{days} days ago"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u64 = 1_700_000_000;
+
+    #[test]
+    fn wilts_without_water() {
+        let garden = Garden::plant(1, START);
+        assert_eq!(garden.wilt(START), Wilt::Healthy);
+        assert_eq!(garden.wilt(START + THIRSTY_AFTER - 1), Wilt::Healthy);
+        assert_eq!(garden.wilt(START + THIRSTY_AFTER), Wilt::Thirsty);
+        assert_eq!(garden.wilt(START + WILTED_AFTER - 1), Wilt::Thirsty);
+        assert_eq!(garden.wilt(START + WILTED_AFTER), Wilt::Wilted);
+        assert_eq!(garden.wilt(START + 100 * DAY), Wilt::Wilted);
+        // a clock turned back counts as just watered
+        assert_eq!(garden.wilt(START - DAY), Wilt::Healthy);
+    }
+
+    #[test]
+    fn visits_grow_for_the_time_away() {
+        let mut garden = Garden::plant(1, START);
+        assert_eq!(garden.branches(), SEEDLING as i32);
+        garden.visit(START + DAY);
+        assert_eq!(garden.branches(), 48);
+        // frequent visits add up to the same
+        for hour in 1..=24 {
+            garden.visit(START + DAY + hour * 60 * 60);
+        }
+        assert_eq!(garden.branches(), 88);
+        assert_eq!(garden.visited, START + 2 * DAY);
+    }
+
+    #[test]
+    fn a_wilted_tree_stops_growing() {
+        let mut garden = Garden::plant(1, START);
+        garden.visit(START + 30 * DAY);
+        assert_eq!(garden.branches(), 8 + 7 * 40);
+        garden.visit(START + 40 * DAY);
+        assert_eq!(garden.branches(), 8 + 7 * 40);
+
+        // watering brings it back, the days it was wilted stay lost
+        garden.water(START + 40 * DAY);
+        assert_eq!(garden.wilt(START + 40 * DAY), Wilt::Healthy);
+        garden.visit(START + 41 * DAY);
+        assert_eq!(garden.branches(), 8 + 8 * 40);
+    }
+
+    #[test]
+    fn leaf_colors_are_left_to_the_theme_while_healthy() {
+        assert_eq!(Wilt::Healthy.leaf_colors(), None);
+        assert_eq!(Wilt::Thirsty.leaf_colors(), Some(THIRSTY_LEAVES));
+        assert_eq!(Wilt::Wilted.leaf_colors(), Some(WILTED_LEAVES));
+    }
+
+    #[test]
+    fn days_ago_reads_naturally() {
+        assert_eq!(days_ago(START, START + 60), "today");
+        assert_eq!(days_ago(START, START + DAY + 60), "yesterday");
+        assert_eq!(days_ago(START, START + 5 * DAY), "5 days ago");
+        assert_eq!(days_ago(START + DAY, START), "today");
+    }
+}
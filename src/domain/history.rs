@@ -0,0 +1,67 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+
+use super::progress;
+
+// a finished focus session, one line of the history file
+pub struct Session {
+    // unix seconds
+    pub end: u64,
+    pub seed: u64,
+    pub minutes: u64,
+    // false when the session was quit early and the tree withered
+    pub completed: bool,
+}
+
+pub fn default_file() -> String {
+    format!("{}/rustbonsai-history", progress::cache_dir())
+}
+
+pub fn log(path: &str, session: &Session) -> anyhow::Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let status = if session.completed {
+        "completed"
+    } else {
+        "withered"
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("couldnt log to {path}"))?;
+    writeln!(
+        file,
+        "{} {} {} {status}",
+        session.end, session.seed, session.minutes
+    )?;
+    Ok(())
+}
+
+// sessions oldest first, empty when nothing has been logged yet
+pub fn load(path: &str) -> anyhow::Result<Vec<Session>> {
+    if !Path::new(path).exists() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(path).with_context(|| format!("couldnt load {path}"))?;
+    let mut sessions = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [end, seed, minutes, status] = fields[..] else {
+            bail!("{path} is not a focus history, bad line {line:?}");
+        };
+        sessions.push(Session {
+            end: end.parse()?,
+            seed: seed.parse()?,
+            minutes: minutes.parse()?,
+            completed: status == "completed",
+        });
+    }
+    Ok(sessions)
+}
@@ -0,0 +1,308 @@
+use anyhow::{bail, Context};
+
+// curses codes of the arrow keys, the ansi backend reports the same ones
+pub const KEY_DOWN: i32 = 0o402;
+pub const KEY_UP: i32 = 0o403;
+pub const KEY_LEFT: i32 = 0o404;
+pub const KEY_RIGHT: i32 = 0o405;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    Quit,
+    Pause,
+    Faster,
+    Slower,
+    NewTree,
+    Regrow,
+    Save,
+    Help,
+    // moving the view of a tree bigger than the screen, with --scroll
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    ZoomOut,
+    ZoomIn,
+    // pruning the finished tree with --prune, which moves with the pan keys
+    Cut,
+    Undo,
+    Redo,
+}
+
+impl Action {
+    const ALL: [Self; 17] = [
+        Self::Quit,
+        Self::Pause,
+        Self::Faster,
+        Self::Slower,
+        Self::NewTree,
+        Self::Regrow,
+        Self::Save,
+        Self::Help,
+        Self::PanUp,
+        Self::PanDown,
+        Self::PanLeft,
+        Self::PanRight,
+        Self::ZoomOut,
+        Self::ZoomIn,
+        Self::Cut,
+        Self::Undo,
+        Self::Redo,
+    ];
+
+    // name used to rebind the action with --keys
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Pause => "pause",
+            Self::Faster => "faster",
+            Self::Slower => "slower",
+            Self::NewTree => "new",
+            Self::Regrow => "regrow",
+            Self::Save => "save",
+            Self::Help => "help",
+            Self::PanUp => "up",
+            Self::PanDown => "down",
+            Self::PanLeft => "left",
+            Self::PanRight => "right",
+            Self::ZoomOut => "zoom-out",
+            Self::ZoomIn => "zoom-in",
+            Self::Cut => "cut",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::Pause => "pause / resume growth",
+            Self::Faster => "grow faster",
+            Self::Slower => "grow slower",
+            Self::NewTree => "new tree with a new seed",
+            Self::Regrow => "regrow the same seed",
+            Self::Save => "save the current tree",
+            Self::Help => "show / hide this help",
+            Self::PanUp => "pan up",
+            Self::PanDown => "pan down",
+            Self::PanLeft => "pan left",
+            Self::PanRight => "pan right",
+            Self::ZoomOut => "zoom out",
+            Self::ZoomIn => "zoom back in",
+            Self::Cut => "cut off the selected branch",
+            Self::Undo => "undo the last cut",
+            Self::Redo => "redo a cut undone",
+        }
+    }
+
+    pub fn moves_view(self) -> bool {
+        matches!(
+            self,
+            Self::PanUp
+                | Self::PanDown
+                | Self::PanLeft
+                | Self::PanRight
+                | Self::ZoomOut
+                | Self::ZoomIn
+        )
+    }
+
+    pub fn prunes(self) -> bool {
+        matches!(self, Self::Cut | Self::Undo | Self::Redo)
+    }
+}
+
+#[derive(Clone)]
+pub struct Keymap {
+    bindings: Vec<(i32, Action)>,
+}
+
+impl Keymap {
+    pub fn action(&self, key: i32) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|&(_, action)| action)
+    }
+
+    pub fn bindings(&self) -> &[(i32, Action)] {
+        &self.bindings
+    }
+
+    // the key bound to an action
+    pub fn key(&self, action: Action) -> Option<i32> {
+        self.bindings
+            .iter()
+            .find(|&&(_, bound)| bound == action)
+            .map(|&(key, _)| key)
+    }
+
+    // bind a key to an action, replacing the action's previous key
+    pub fn bind(&mut self, key: i32, action: Action) {
+        self.bindings.retain(|&(_, bound)| bound != action);
+        self.bindings.push((key, action));
+    }
+
+    // apply comma-delimited ACTION=KEY overrides, e.g. "pause=p,quit=x". a key left bound to
+    // two actions is an error, so keys can be swapped but not lost
+    pub fn parse_overrides(&mut self, list: &str) -> anyhow::Result<()> {
+        let mut keymap = self.clone();
+        for binding in list.split(',').filter(|binding| !binding.is_empty()) {
+            let (name, key) = binding
+                .split_once('=')
+                .with_context(|| format!("expected ACTION=KEY, got {binding:?}"))?;
+            let action = Action::ALL
+                .into_iter()
+                .find(|action| action.name() == name)
+                .with_context(|| format!("unknown action {name:?}"))?;
+            keymap.bind(parse_key(key)?, action);
+        }
+        for (i, &(key, action)) in keymap.bindings.iter().enumerate() {
+            if let Some(&(_, other)) = keymap.bindings[i + 1..].iter().find(|&&(k, _)| k == key) {
+                bail!(
+                    "{} is bound to both {} and {}, bind one of them to another key",
+                    key_name(key),
+                    action.name(),
+                    other.name()
+                );
+            }
+        }
+        *self = keymap;
+        Ok(())
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                ('q' as i32, Action::Quit),
+                (' ' as i32, Action::Pause),
+                ('+' as i32, Action::Faster),
+                ('-' as i32, Action::Slower),
+                ('n' as i32, Action::NewTree),
+                ('r' as i32, Action::Regrow),
+                ('s' as i32, Action::Save),
+                ('?' as i32, Action::Help),
+                (KEY_UP, Action::PanUp),
+                (KEY_DOWN, Action::PanDown),
+                (KEY_LEFT, Action::PanLeft),
+                (KEY_RIGHT, Action::PanRight),
+                ('z' as i32, Action::ZoomOut),
+                ('Z' as i32, Action::ZoomIn),
+                ('x' as i32, Action::Cut),
+                ('u' as i32, Action::Undo),
+                ('U' as i32, Action::Redo),
+            ],
+        }
+    }
+}
+
+// printable name of a key, the inverse of parse_key
+pub fn key_name(key: i32) -> String {
+    if let Some(&(name, _)) = ARROWS.iter().find(|&&(_, arrow)| arrow == key) {
+        return name.to_owned();
+    }
+    match u8::try_from(key) {
+        Ok(b' ') => "space".to_owned(),
+        Ok(byte) if byte.is_ascii_graphic() => char::from(byte).to_string(),
+        _ => format!("#{key}"),
+    }
+}
+
+const ARROWS: [(&str, i32); 4] = [
+    ("up", KEY_UP),
+    ("down", KEY_DOWN),
+    ("left", KEY_LEFT),
+    ("right", KEY_RIGHT),
+];
+
+fn parse_key(key: &str) -> anyhow::Result<i32> {
+    if let Some(&(_, arrow)) = ARROWS.iter().find(|&&(name, _)| name == key) {
+        return Ok(arrow);
+    }
+    let mut chars = key.chars();
+    match (key, chars.next(), chars.next()) {
+        ("space", _, _) => Ok(' ' as i32),
+        (_, Some(ch), None) if ch.is_ascii_graphic() => Ok(ch as i32),
+        _ => bail!("keys must be a single printable character, \"space\" or an arrow, got {key:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_move_an_action_to_another_key() {
+        let mut keymap = Keymap::default();
+        keymap
+            .parse_overrides("pause=p,quit=Q,new=N,left=h,cut=d")
+            .unwrap();
+        assert_eq!(keymap.action('p' as i32), Some(Action::Pause));
+        assert_eq!(keymap.action('Q' as i32), Some(Action::Quit));
+        assert_eq!(keymap.action('N' as i32), Some(Action::NewTree));
+        assert_eq!(keymap.action('h' as i32), Some(Action::PanLeft));
+        assert_eq!(keymap.key(Action::Cut), Some('d' as i32));
+        // the old keys are free again
+        assert_eq!(keymap.action(' ' as i32), None);
+        assert_eq!(keymap.action('q' as i32), None);
+        assert_eq!(keymap.action(KEY_LEFT), None);
+        assert_eq!(keymap.action('x' as i32), None);
+        assert_eq!(keymap.bindings().len(), Keymap::default().bindings().len());
+    }
+
+    #[test]
+    fn overrides_can_swap_keys() {
+        let mut keymap = Keymap::default();
+        keymap.parse_overrides("pause=q,quit=space").unwrap();
+        assert_eq!(keymap.action('q' as i32), Some(Action::Pause));
+        assert_eq!(keymap.action(' ' as i32), Some(Action::Quit));
+    }
+
+    #[test]
+    fn rejects_overrides_that_take_a_bound_key() {
+        for list in ["pause=q", "regrow=up", "undo=x", "pause=p,quit=p"] {
+            let mut keymap = Keymap::default();
+            assert!(keymap.parse_overrides(list).is_err(), "{list:?}");
+            // nothing is applied
+            assert_eq!(keymap.bindings(), Keymap::default().bindings());
+        }
+    }
+
+    #[test]
+    fn no_overrides_keep_the_defaults() {
+        let mut keymap = Keymap::default();
+        keymap.parse_overrides("").unwrap();
+        keymap.parse_overrides(",,").unwrap();
+        assert_eq!(keymap.bindings(), Keymap::default().bindings());
+    }
+
+    #[test]
+    fn rejects_malformed_overrides() {
+        for list in [
+            "pause",
+            "pause:p",
+            "fly=f",
+            "=p",
+            "pause=",
+            "pause=pp",
+            "pause=tab",
+            "pause=\t",
+            "pause=é",
+            "pause=p,quit",
+        ] {
+            assert!(Keymap::default().parse_overrides(list).is_err(), "{list:?}");
+        }
+    }
+
+    #[test]
+    fn key_names_parse_back() {
+        for (key, _) in Keymap::default().bindings() {
+            assert_eq!(parse_key(&key_name(*key)).unwrap(), *key);
+        }
+        assert_eq!(key_name(' ' as i32), "space");
+        assert_eq!(key_name(KEY_RIGHT), "right");
+        assert_eq!(key_name(9), "#9");
+    }
+}
@@ -0,0 +1,467 @@
+use crate::grower::Grower;
+
+use super::{
+    base::base_size,
+    config::{Align, BaseType, Config},
+};
+
+// smallest screen a tree is grown on
+pub const MIN_COLS: i32 = 16;
+pub const MIN_ROWS: i32 = 6;
+// rows left for the tree before a base is dropped for a smaller one
+const MIN_TREE_ROWS: i32 = 4;
+// tree area the default life and multiplier are tuned for, 80x24 above the big base
+const FULL_AREA: f64 = 80.0 * 20.0;
+
+// --fit stops rerolling seeds once a tree comes this close to the fill asked for
+const FIT_TOLERANCE: f64 = 0.05;
+const FIT_REROLLS: u64 = 8;
+const FIT_LIFE: (i32, i32) = (4, 150);
+// steps a trial tree may take before it counts as too big, growth is exponential in life
+const TRIAL_STEPS: u32 = 200_000;
+
+// where the tree, its base and the message go on a screen, and how much the tree may grow there
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Layout {
+    pub rows: i32,
+    pub cols: i32,
+    // the base asked for, or a smaller one that fits
+    pub base_type: BaseType,
+    pub base_y: i32,
+    pub base_x: i32,
+    // the tree grows above the base from the top of the screen, its root in the middle of
+    // the bottom row. tree coordinates are relative to the left edge
+    pub tree_x: i32,
+    pub tree_rows: i32,
+    pub tree_cols: i32,
+    // the column kept for the message, 0 wide when the message goes over the tree
+    pub message_x: i32,
+    pub message_cols: i32,
+    pub seed: u64,
+    pub life_start: i32,
+    pub multiplier: i32,
+}
+
+impl Layout {
+    // none when the screen is too small for any tree
+    pub fn new(config: &Config, rows: i32, cols: i32) -> Option<Self> {
+        if rows < MIN_ROWS || cols < MIN_COLS {
+            return None;
+        }
+
+        // the message column goes on the side away from the tree, if the tree still fits
+        let message_cols = if config.message_column && !config.message.is_empty() {
+            message_width(cols) + 4
+        } else {
+            0
+        };
+        let message_cols = if cols - message_cols >= MIN_COLS {
+            message_cols
+        } else {
+            0
+        };
+        let (area_x, message_x) = match config.align {
+            Align::Right => (message_cols, 0),
+            Align::Left | Align::Center => (0, cols - message_cols),
+        };
+        let area_cols = cols - message_cols;
+        let area_end = area_x + area_cols;
+
+        // an aligned tree keeps to its half of the screen. the offset moves the root, and the
+        // tree area narrows evenly around it rather than run off the screen
+        let span = match config.align {
+            Align::Center => area_cols,
+            Align::Left | Align::Right => (area_cols / 2).clamp(MIN_COLS, area_cols),
+        };
+        let root_x = match config.align {
+            Align::Left => area_x + span / 2,
+            Align::Center => area_x + area_cols / 2,
+            Align::Right => area_end - span + span / 2,
+        };
+        let root_x =
+            (root_x + config.offset.0).clamp(area_x + MIN_COLS / 2, area_end - MIN_COLS / 2);
+        let left = (span / 2).min(root_x - area_x).min(area_end - root_x);
+        let right = (area_end - root_x).min(left + 1).min(span - span / 2);
+        let (tree_x, tree_cols) = (root_x - left, left + right);
+
+        // lifted off the bottom, but never so far that no tree fits underneath the top
+        let bottom = rows - config.offset.1.clamp(0, rows - MIN_ROWS);
+
+        let base_type = [config.base_type, BaseType::Small, BaseType::None]
+            .into_iter()
+            .filter(|&base_type| base_size(&base_type) <= base_size(&config.base_type))
+            .find(|base_type| {
+                let (width, height) = base_size(base_type);
+                width <= tree_cols && bottom - height >= MIN_TREE_ROWS
+            })
+            .unwrap_or(BaseType::None);
+        let (base_width, base_height) = base_size(&base_type);
+        let tree_rows = bottom - base_height;
+
+        // a smaller screen grows a smaller tree, rather than one squashed against the edges
+        let scale = if config.shrink_to_fit {
+            (f64::from(tree_rows * tree_cols) / FULL_AREA)
+                .sqrt()
+                .min(1.0)
+        } else {
+            1.0
+        };
+        let life_start = scaled(config.life_start, scale, 4);
+        let multiplier = scaled(config.multiplier, scale, 2);
+
+        let mut layout = Self {
+            rows,
+            cols,
+            base_type,
+            base_y: bottom - base_height,
+            base_x: root_x - base_width / 2,
+            tree_x,
+            tree_rows,
+            tree_cols,
+            message_x,
+            message_cols,
+            seed: config.seed,
+            life_start,
+            multiplier,
+        };
+        if let Some((life, multiplier)) = config.growth {
+            (layout.life_start, layout.multiplier) = (life, multiplier);
+        } else if let Some(fill) = config.fit {
+            layout.fit(config, fill);
+        }
+        Some(layout)
+    }
+
+    // width the message is wrapped to
+    pub fn message_width(&self) -> i32 {
+        if self.message_cols > 0 {
+            self.message_cols - 4
+        } else {
+            message_width(self.cols)
+        }
+    }
+
+    // top left corner of a message box with text width x height: in the message column, or
+    // to the lower right of the tree like cbonsai, moved back onto the screen when too long
+    pub fn message_at(&self, width: i32, height: i32) -> (i32, i32) {
+        let bottom = self.base_y + base_size(&self.base_type).1;
+        let y = ((bottom as f32 * 0.7) as i32 - 1)
+            .min(bottom - height - 2)
+            .max(0);
+        if self.message_cols > 0 {
+            return (y, self.message_x);
+        }
+        let x = ((self.tree_x as f32 + self.tree_cols as f32 * 0.7) as i32 - 2)
+            .min(self.cols - width - 4)
+            .max(0);
+        (y, x)
+    }
+
+    // pick a life, multiplier and seed whose tree covers about fill of the tree area without
+    // running off it, by growing trial trees. the first close enough wins, and the seed is
+    // rerolled only when no life and multiplier come close. the same seed always fits the
+    // same way
+    pub fn fit(&mut self, config: &Config, fill: f64) {
+        let mut multipliers = vec![
+            config.multiplier,
+            config.multiplier / 2,
+            config.multiplier * 3 / 2,
+        ];
+        multipliers.iter_mut().for_each(|m| *m = (*m).clamp(2, 20));
+        multipliers.dedup();
+
+        // (miss, seed, life, multiplier) of the closest tree so far
+        let mut best: Option<(f64, u64, i32, i32)> = None;
+        'rerolls: for reroll in 0..FIT_REROLLS {
+            let seed = config
+                .seed
+                .wrapping_add(reroll.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            for &multiplier in &multipliers {
+                // bigger life grows a bigger tree, give or take the dice
+                let (mut low, mut high) = FIT_LIFE;
+                while low <= high {
+                    let life = (low + high) / 2;
+                    let Some(covered) = self.trial(config, seed, life, multiplier, fill) else {
+                        high = life - 1;
+                        continue;
+                    };
+                    let miss = (covered - fill).abs();
+                    if best.is_none_or(|(closest, ..)| miss < closest) {
+                        best = Some((miss, seed, life, multiplier));
+                    }
+                    if miss <= FIT_TOLERANCE {
+                        break 'rerolls;
+                    }
+                    if covered > fill {
+                        high = life - 1;
+                    } else {
+                        low = life + 1;
+                    }
+                }
+            }
+        }
+
+        if let Some((_, seed, life, multiplier)) = best {
+            (self.seed, self.life_start, self.multiplier) = (seed, life, multiplier);
+        }
+    }
+
+    // fraction of the tree area covered by the bounds of a fully grown trial tree. none if it
+    // runs off the area, or stopped early once it covers more than fill allows
+    fn trial(
+        &self,
+        config: &Config,
+        seed: u64,
+        life: i32,
+        multiplier: i32,
+        fill: f64,
+    ) -> Option<f64> {
+        let (rows, cols) = (self.tree_rows, self.tree_cols);
+        let most = ((fill + FIT_TOLERANCE) * f64::from(rows * cols)) as i32;
+        let mut grower = Grower::seeded(config, seed, rows, cols, life, multiplier);
+        while !grower.is_done() {
+            grower.step();
+            if grower.spilled() || grower.steps() > TRIAL_STEPS || covered(&grower) > most {
+                return None;
+            }
+        }
+        Some(f64::from(covered(&grower)) / f64::from(rows * cols))
+    }
+}
+
+// cells inside the bounds of the tree so far
+fn covered(grower: &Grower) -> i32 {
+    grower.bounds().map_or(0, |(top, left, bottom, right)| {
+        (bottom - top + 1) * (right - left + 1)
+    })
+}
+
+fn message_width(cols: i32) -> i32 {
+    (cols / 4).max(8)
+}
+
+// value shrunk by scale, but not below floor unless it already was
+fn scaled(value: i32, scale: f64, floor: i32) -> i32 {
+    ((f64::from(value) * scale).round() as i32).max(value.min(floor))
+}
+
+// the part of text written at (y, x) that lands inside rows x cols, as (x, text)
+pub fn clip(y: i32, x: i32, text: &str, rows: i32, cols: i32) -> Option<(i32, String)> {
+    if !(0..rows).contains(&y) {
+        return None;
+    }
+    let skip = (-x).max(0);
+    let visible: String = text
+        .chars()
+        .skip(skip as usize)
+        .take((cols - x - skip).max(0) as usize)
+        .collect();
+    (!visible.is_empty()).then_some((x + skip, visible))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(config: &Config, rows: i32, cols: i32) -> Layout {
+        Layout::new(config, rows, cols).unwrap()
+    }
+
+    #[test]
+    fn centers_the_tree_over_the_base() {
+        let layout = layout(&Config::default(), 24, 80);
+        assert_eq!(layout.base_type, BaseType::Big);
+        assert_eq!((layout.base_y, layout.base_x), (20, 25));
+        assert_eq!(
+            (layout.tree_x, layout.tree_rows, layout.tree_cols),
+            (0, 20, 80)
+        );
+        assert_eq!((layout.life_start, layout.multiplier), (64, 10));
+    }
+
+    #[test]
+    fn refuses_screens_too_small_for_a_tree() {
+        let config = Config::default();
+        assert!(Layout::new(&config, MIN_ROWS - 1, 80).is_none());
+        assert!(Layout::new(&config, 24, MIN_COLS - 1).is_none());
+        assert!(Layout::new(&config, MIN_ROWS, MIN_COLS).is_some());
+    }
+
+    #[test]
+    fn drops_to_a_smaller_base_that_fits() {
+        let config = Config::default();
+        // too narrow for the big base
+        assert_eq!(layout(&config, 8, 20).base_type, BaseType::Small);
+        assert_eq!(layout(&config, 8, 20).tree_rows, 5);
+        // too short to keep rows for the tree above any base
+        assert_eq!(layout(&config, 6, 20).base_type, BaseType::None);
+        assert_eq!(layout(&config, 6, 20).tree_rows, 6);
+        // never a bigger base than asked for
+        let small = Config {
+            base_type: BaseType::Small,
+            ..Config::default()
+        };
+        assert_eq!(layout(&small, 24, 80).base_type, BaseType::Small);
+    }
+
+    #[test]
+    fn grows_a_smaller_tree_on_a_smaller_screen() {
+        let config = Config::default();
+        let tiny = layout(&config, 6, 20);
+        assert_eq!((tiny.life_start, tiny.multiplier), (18, 3));
+        // bigger screens keep the configured size
+        let big = layout(&config, 60, 200);
+        assert_eq!((big.life_start, big.multiplier), (64, 10));
+        let unscaled = Config {
+            shrink_to_fit: false,
+            ..Config::default()
+        };
+        let tiny = layout(&unscaled, 6, 20);
+        assert_eq!((tiny.life_start, tiny.multiplier), (64, 10));
+        // what was already below the floor stays as it was
+        let sparse = Config {
+            life_start: 2,
+            multiplier: 1,
+            ..Config::default()
+        };
+        let tiny = layout(&sparse, 6, 20);
+        assert_eq!((tiny.life_start, tiny.multiplier), (2, 1));
+    }
+
+    #[test]
+    fn keeps_odd_widths_inside_the_screen() {
+        let layout = layout(&Config::default(), 24, 17);
+        assert_eq!((layout.tree_x, layout.tree_cols), (0, 17));
+        assert_eq!((layout.base_type, layout.base_x), (BaseType::Small, 1));
+    }
+
+    // fraction of the tree area a fully grown tree covers, none if it ran off
+    fn coverage(config: &Config, layout: &Layout) -> Option<f64> {
+        let mut grower = Grower::fitted(config, layout);
+        while !grower.is_done() {
+            grower.step();
+        }
+        let area = f64::from(layout.tree_rows * layout.tree_cols);
+        (!grower.spilled()).then(|| f64::from(covered(&grower)) / area)
+    }
+
+    #[test]
+    fn fits_the_tree_to_the_fill_asked_for() {
+        for fill in [0.3, 0.6] {
+            let config = Config {
+                seed: 7,
+                fit: Some(fill),
+                ..Config::default()
+            };
+            let fitted = layout(&config, 24, 80);
+            assert!((FIT_LIFE.0..=FIT_LIFE.1).contains(&fitted.life_start));
+            assert!((2..=20).contains(&fitted.multiplier));
+            let covered = coverage(&config, &fitted).unwrap();
+            assert!(
+                (covered - fill).abs() <= FIT_TOLERANCE,
+                "{covered} for {fill}"
+            );
+            // the same seed fits the same way
+            let again = layout(&config, 24, 80);
+            let picked = |l: &Layout| (l.seed, l.life_start, l.multiplier);
+            assert_eq!(picked(&fitted), picked(&again));
+        }
+    }
+
+    #[test]
+    fn keeps_the_growth_it_was_given_over_fitting() {
+        let config = Config {
+            fit: Some(0.5),
+            growth: Some((40, 6)),
+            ..Config::default()
+        };
+        let layout = layout(&config, 24, 80);
+        assert_eq!(
+            (layout.seed, layout.life_start, layout.multiplier),
+            (config.seed, 40, 6)
+        );
+    }
+
+    fn placed(align: Align, offset: (i32, i32)) -> Layout {
+        let config = Config {
+            align,
+            offset,
+            ..Config::default()
+        };
+        layout(&config, 24, 80)
+    }
+
+    #[test]
+    fn aligns_the_tree_to_a_side() {
+        let left = placed(Align::Left, (0, 0));
+        assert_eq!((left.tree_x, left.tree_cols, left.base_x), (0, 40, 5));
+        let right = placed(Align::Right, (0, 0));
+        assert_eq!((right.tree_x, right.tree_cols, right.base_x), (40, 40, 45));
+    }
+
+    #[test]
+    fn offsets_the_tree_and_base() {
+        let moved = placed(Align::Center, (5, 2));
+        assert_eq!((moved.tree_x, moved.tree_cols, moved.base_x), (10, 70, 30));
+        assert_eq!((moved.base_y, moved.tree_rows), (18, 18));
+        // too far is held back on the screen, with rows left for a tree
+        let far = placed(Align::Center, (-100, 100));
+        assert_eq!((far.tree_x, far.tree_cols), (0, 17));
+        assert_eq!((far.base_type, far.tree_rows), (BaseType::None, MIN_ROWS));
+    }
+
+    #[test]
+    fn keeps_a_column_for_the_message() {
+        let config = Config {
+            message: "hello".to_owned(),
+            message_column: true,
+            ..Config::default()
+        };
+        let beside = layout(&config, 24, 80);
+        assert_eq!((beside.message_x, beside.message_cols), (56, 24));
+        assert_eq!((beside.tree_x, beside.tree_cols), (0, 56));
+        assert_eq!(beside.message_width(), 20);
+        assert_eq!(beside.message_at(5, 1).1, 56);
+
+        let right = Config {
+            align: Align::Right,
+            ..config.clone()
+        };
+        let right = layout(&right, 24, 80);
+        assert_eq!(
+            (right.message_x, right.tree_x, right.tree_cols),
+            (0, 52, 28)
+        );
+        // the tree comes first when both dont fit
+        assert_eq!(layout(&config, 24, 20).message_cols, 0);
+        let empty = Config {
+            message: String::new(),
+            ..config
+        };
+        assert_eq!(layout(&empty, 24, 80).message_cols, 0);
+    }
+
+    #[test]
+    fn moves_a_long_message_back_on_screen() {
+        let layout = layout(&Config::default(), 24, 80);
+        let (y, x) = layout.message_at(70, 2);
+        assert_eq!(x, 6);
+        assert!(y >= 0 && y + 2 + 2 <= 24);
+        assert_eq!(layout.message_at(10, 30), (0, 54));
+    }
+
+    #[test]
+    fn clips_text_to_the_screen() {
+        assert_eq!(clip(0, 2, "abc", 4, 10), Some((2, "abc".to_owned())));
+        assert_eq!(clip(1, -2, "abcd", 4, 10), Some((0, "cd".to_owned())));
+        assert_eq!(clip(1, 8, "abcd", 4, 10), Some((8, "ab".to_owned())));
+        assert_eq!(
+            clip(1, -1, "abcdefghijkl", 4, 10),
+            Some((0, "bcdefghijk".to_owned()))
+        );
+        assert_eq!(clip(-1, 0, "abc", 4, 10), None);
+        assert_eq!(clip(4, 0, "abc", 4, 10), None);
+        assert_eq!(clip(0, 10, "abc", 4, 10), None);
+        assert_eq!(clip(0, -3, "abc", 4, 10), None);
+    }
+}
@@ -1,4 +1,16 @@
+pub mod base;
 pub mod branch_type;
+pub mod canvas;
 pub mod config;
-pub mod counters;
-pub mod ncurses_objects;
+pub mod falling;
+pub mod feed;
+pub mod garden;
+pub mod history;
+pub mod keymap;
+pub mod layout;
+pub mod progress;
+pub mod sky;
+pub mod theme;
+pub mod topology;
+pub mod transition;
+pub mod viewport;
@@ -0,0 +1,129 @@
+use std::{env, fs, path::Path};
+
+use anyhow::{bail, Context};
+
+// $XDG_CACHE_HOME, falling back to $HOME/.cache
+pub fn cache_dir() -> String {
+    match (env::var("XDG_CACHE_HOME"), env::var("HOME")) {
+        (Ok(cache), _) if !cache.is_empty() => cache,
+        (_, Ok(home)) => format!("{home}/.cache"),
+        _ => ".".to_owned(),
+    }
+}
+
+pub fn default_file() -> String {
+    format!("{}/cbonsai", cache_dir())
+}
+
+// a tree saved to be regrown later
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Saved {
+    pub seed: u64,
+    pub branches: i32,
+    pub cuts: Vec<usize>,
+    // the life and multiplier it grew with, after --fit or a small screen picked them. none
+    // in files cbonsai wrote
+    pub growth: Option<(i32, i32)>,
+}
+
+// save the seed, how many branches have grown, which were pruned and what it grew with, so
+// the tree can be regrown later. the first two fields match cbonsai's format
+pub fn save(path: &str, saved: &Saved) -> anyhow::Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, format(saved) + "\n").with_context(|| format!("couldnt save to {path}"))
+}
+
+pub fn load(path: &str) -> anyhow::Result<Saved> {
+    let text = fs::read_to_string(path).with_context(|| format!("couldnt load {path}"))?;
+    parse(&text).with_context(|| format!("{path} is not a saved tree"))
+}
+
+fn format(saved: &Saved) -> String {
+    let mut text = format!("{} {}", saved.seed, saved.branches);
+    if !saved.cuts.is_empty() {
+        let cuts: Vec<String> = saved.cuts.iter().map(usize::to_string).collect();
+        text = format!("{text} {}", cuts.join(","));
+    }
+    if let Some((life, multiplier)) = saved.growth {
+        text = format!("{text} life={life} multiplier={multiplier}");
+    }
+    text
+}
+
+// cuts and growth are optional, growth is told apart by its names
+fn parse(text: &str) -> anyhow::Result<Saved> {
+    let mut fields = text.split_whitespace();
+    let (Some(seed), Some(branches)) = (fields.next(), fields.next()) else {
+        bail!("expected a seed and a branch count");
+    };
+    let mut saved = Saved {
+        seed: seed.parse()?,
+        branches: branches.parse()?,
+        ..Saved::default()
+    };
+    let (mut life, mut multiplier) = (None, None);
+    for field in fields {
+        match field.split_once('=') {
+            Some(("life", value)) => life = Some(value.parse()?),
+            Some(("multiplier", value)) => multiplier = Some(value.parse()?),
+            Some((key, _)) => bail!("unknown field {key:?}"),
+            None => saved.cuts = field.split(',').map(str::parse).collect::<Result<_, _>>()?,
+        }
+    }
+    saved.growth = life.zip(multiplier);
+    Ok(saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        for saved in [
+            Saved {
+                seed: 7,
+                branches: 120,
+                ..Saved::default()
+            },
+            Saved {
+                seed: u64::MAX,
+                branches: 3,
+                cuts: vec![4, 12],
+                growth: Some((106, 10)),
+            },
+            Saved {
+                seed: 1,
+                branches: 0,
+                cuts: Vec::new(),
+                growth: Some((4, 2)),
+            },
+        ] {
+            assert_eq!(parse(&format(&saved)).unwrap(), saved);
+        }
+    }
+
+    #[test]
+    fn reads_cbonsai_files() {
+        let saved = parse("12345 500\n").unwrap();
+        assert_eq!((saved.seed, saved.branches), (12345, 500));
+        assert!(saved.cuts.is_empty());
+        assert_eq!(saved.growth, None);
+    }
+
+    #[test]
+    fn rejects_what_is_not_a_saved_tree() {
+        for text in [
+            "",
+            "12345",
+            "seed 500",
+            "1 2 3,x",
+            "1 2 life=x",
+            "1 2 size=3",
+        ] {
+            assert!(parse(text).is_err(), "{text:?}");
+        }
+    }
+}
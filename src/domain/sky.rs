@@ -0,0 +1,364 @@
+use std::{str::FromStr, time::Duration};
+
+use anyhow::bail;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::canvas::{Attr, Canvas, Cell};
+
+// time between moves of the sky, slow enough to leave the cpu alone
+pub const TICK: Duration = Duration::from_millis(150);
+
+// what the sky behind the tree looks like
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Weather {
+    // no sky, the terminal background
+    #[default]
+    None,
+    // a sun and a few clouds
+    Day,
+    // stars and a moon
+    Night,
+    Clouds,
+    Rain,
+    // snow that settles on the branches
+    Snow,
+    // one of the others, picked again for every tree
+    Random,
+}
+
+impl FromStr for Weather {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Ok(match value {
+            "none" => Self::None,
+            "day" => Self::Day,
+            "night" => Self::Night,
+            "clouds" => Self::Clouds,
+            "rain" => Self::Rain,
+            "snow" => Self::Snow,
+            "random" => Self::Random,
+            _ => bail!(
+                "unknown weather {value:?}, use none, day, night, clouds, rain, snow or random"
+            ),
+        })
+    }
+}
+
+const PICKED: [Weather; 5] = [
+    Weather::Day,
+    Weather::Night,
+    Weather::Clouds,
+    Weather::Rain,
+    Weather::Snow,
+];
+
+const SUN: [&str; 3] = [" \\ | / ", "-- O --", " / | \\ "];
+const MOON: [&str; 3] = [" .-. ", "(   )", " `-' "];
+const CLOUDS: [[&str; 3]; 2] = [
+    ["   .--.    ", " .(    ).  ", "(___.__)__)"],
+    ["  _  _   ", "_( )( )_ ", "(_ .  _ )"],
+];
+const STARS: [char; 5] = ['.', '.', '+', '*', '\''];
+
+const SUN_ATTR: Attr = Attr::new(11, true);
+const MOON_ATTR: Attr = Attr::new(7, true);
+const STAR_ATTR: Attr = Attr::new(7, false);
+const RAIN_ATTR: Attr = Attr::new(12, false);
+const SNOW_ATTR: Attr = Attr::new(15, true);
+
+// rows a raindrop and a snowflake fall in a tick
+const RAIN_FALL: f64 = 1.0;
+const SNOW_FALL: f64 = 0.35;
+
+struct Cloud {
+    y: i32,
+    x: f64,
+    speed: f64,
+    shape: usize,
+}
+
+struct Star {
+    y: i32,
+    x: i32,
+    ch: char,
+    lit: bool,
+}
+
+// rain or snow on its way down
+struct Drop {
+    y: f64,
+    x: f64,
+}
+
+// a backdrop for the tree, with the sun or moon, stars, clouds drifting by and rain or snow
+// falling. snow settles on whatever it lands on
+pub struct Sky {
+    weather: Weather,
+    rng: StdRng,
+    rows: i32,
+    cols: i32,
+    stars: Vec<Star>,
+    clouds: Vec<Cloud>,
+    cloud_attr: Attr,
+    drops: Vec<Drop>,
+    settled: Canvas,
+}
+
+impl Sky {
+    // none for no weather. the seed picks random weather and places everything, the same
+    // seed makes the same sky
+    pub fn new(weather: Weather, rows: i32, cols: i32, seed: u64) -> Option<Self> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let weather = match weather {
+            Weather::None => return None,
+            Weather::Random => PICKED[rng.gen_range(0..PICKED.len())],
+            weather => weather,
+        };
+
+        // stars keep to the upper part of the sky, clear of the ground
+        let stars = match weather {
+            Weather::Night => (0..rows * cols / 30)
+                .map(|_| Star {
+                    y: rng.gen_range(0..(rows * 2 / 3).max(1)),
+                    x: rng.gen_range(0..cols.max(1)),
+                    ch: STARS[rng.gen_range(0..STARS.len())],
+                    lit: true,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let (count, cloud_attr) = match weather {
+            Weather::Day => (2, Attr::new(15, false)),
+            Weather::Night => (1, Attr::new(8, false)),
+            Weather::Clouds => (cols / 16, Attr::new(7, false)),
+            _ => (cols / 10, Attr::new(8, false)),
+        };
+        let clouds = (0..count)
+            .map(|_| Cloud {
+                y: rng.gen_range(0..(rows / 4).max(1)),
+                x: rng.gen_range(-10.0..f64::from(cols)),
+                speed: rng.gen_range(0.03..0.12),
+                shape: rng.gen_range(0..CLOUDS.len()),
+            })
+            .collect();
+
+        Some(Self {
+            weather,
+            rng,
+            rows,
+            cols,
+            stars,
+            clouds,
+            cloud_attr,
+            drops: Vec::new(),
+            settled: Canvas::new(cols, rows),
+        })
+    }
+
+    // move everything along. ground holds what is in front of the sky, the tree and its
+    // base, which rain and snow stop at
+    pub fn tick(&mut self, ground: &Canvas) {
+        for cloud in &mut self.clouds {
+            cloud.x += cloud.speed;
+            // back in from the left once gone off the right
+            if cloud.x > f64::from(self.cols) {
+                cloud.x = -(CLOUDS[cloud.shape][2].len() as f64);
+            }
+        }
+
+        if !self.stars.is_empty() {
+            for _ in 0..2 {
+                let i = self.rng.gen_range(0..self.stars.len());
+                self.stars[i].lit = !self.stars[i].lit;
+            }
+        }
+
+        let (spawn, fall) = match self.weather {
+            Weather::Rain => (self.cols / 6, RAIN_FALL),
+            Weather::Snow => ((self.cols / 25).max(1), SNOW_FALL),
+            _ => return,
+        };
+        for _ in 0..spawn {
+            self.drops.push(Drop {
+                y: f64::from(self.rng.gen_range(0..(self.rows / 4).max(1))),
+                x: self
+                    .rng
+                    .gen_range(0.0..f64::from(self.cols + self.rows / 2)),
+            });
+        }
+
+        let snow = self.weather == Weather::Snow;
+        let filled = |y: i32, x: i32| ground.get(y, x).is_some_and(|cell| cell.ch != ' ');
+        let (rng, settled, rows) = (&mut self.rng, &mut self.settled, self.rows);
+        self.drops.retain_mut(|drop| {
+            let (y, x) = (drop.y.round() as i32, drop.x.round() as i32);
+            drop.y += fall;
+            // rain slants in the wind, snow wanders
+            drop.x += if snow { rng.gen_range(-0.3..0.3) } else { -0.5 };
+            let (next_y, next_x) = (drop.y.round() as i32, drop.x.round() as i32);
+            if next_y == y {
+                return true;
+            }
+            let landed = next_y >= rows || filled(next_y, next_x);
+            if !landed {
+                return settled.get(next_y, next_x).is_none();
+            }
+            // a flake settles where it was, on top of what it hit
+            if snow && !filled(y, x) && settled.get(y, x).is_none() {
+                let y = y.min(rows - 1);
+                settled.set(
+                    y,
+                    x,
+                    Some(Cell {
+                        ch: '.',
+                        attr: SNOW_ATTR,
+                    }),
+                );
+            }
+            false
+        });
+    }
+
+    pub fn frame(&self) -> Canvas {
+        let mut frame = Canvas::new(self.cols, self.rows);
+        for star in self.stars.iter().filter(|star| star.lit) {
+            frame.set(
+                star.y,
+                star.x,
+                Some(Cell {
+                    ch: star.ch,
+                    attr: STAR_ATTR,
+                }),
+            );
+        }
+
+        let corner = match self.weather {
+            Weather::Day => Some((SUN, SUN_ATTR)),
+            Weather::Night => Some((MOON, MOON_ATTR)),
+            _ => None,
+        };
+        if let Some((art, attr)) = corner {
+            for (y, line) in (1..).zip(art) {
+                frame.put_str(y, self.cols - line.len() as i32 - 4, line, attr);
+            }
+        }
+
+        let (ch, attr) = match self.weather {
+            Weather::Snow => ('*', SNOW_ATTR),
+            _ => ('/', RAIN_ATTR),
+        };
+        for drop in &self.drops {
+            frame.set(
+                drop.y.round() as i32,
+                drop.x.round() as i32,
+                Some(Cell { ch, attr }),
+            );
+        }
+        // rain and snow come out from under the clouds
+        for cloud in &self.clouds {
+            for (y, line) in (cloud.y..).zip(CLOUDS[cloud.shape]) {
+                frame.put_str(y, cloud.x.round() as i32, line, self.cloud_attr);
+            }
+        }
+        frame.paste(0, 0, &self.settled);
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(canvas: &Canvas) -> Vec<(i32, i32, char)> {
+        (0..canvas.height)
+            .flat_map(|y| (0..canvas.width).map(move |x| (y, x)))
+            .filter_map(|(y, x)| Some((y, x, canvas.get(y, x)?.ch)))
+            .collect()
+    }
+
+    // a flat ground line across row 10 of a 20x60 screen
+    fn ground() -> Canvas {
+        let mut ground = Canvas::new(60, 20);
+        ground.put_str(10, 0, &"=".repeat(60), Attr::new(2, false));
+        ground
+    }
+
+    #[test]
+    fn parses_weather() {
+        assert_eq!("snow".parse::<Weather>().unwrap(), Weather::Snow);
+        assert!("hail".parse::<Weather>().is_err());
+        assert!(Sky::new(Weather::None, 20, 60, 7).is_none());
+    }
+
+    #[test]
+    fn picks_random_weather_from_the_seed() {
+        for seed in 0..20 {
+            let sky = Sky::new(Weather::Random, 20, 60, seed).unwrap();
+            assert!(PICKED.contains(&sky.weather));
+            assert_eq!(
+                sky.weather,
+                Sky::new(Weather::Random, 20, 60, seed).unwrap().weather
+            );
+        }
+    }
+
+    #[test]
+    fn puts_the_sun_and_moon_in_the_corner() {
+        let day = Sky::new(Weather::Day, 20, 60, 7).unwrap().frame();
+        let sun: String = (49..56)
+            .filter_map(|x| day.get(2, x))
+            .map(|cell| cell.ch)
+            .collect();
+        assert_eq!(sun, SUN[1]);
+
+        let night = Sky::new(Weather::Night, 20, 60, 7).unwrap();
+        assert_eq!(night.stars.len(), 40);
+        assert!(night.stars.iter().all(|star| star.y < 13));
+        assert_eq!(night.frame().get(2, 51).map(|cell| cell.ch), Some('('));
+    }
+
+    #[test]
+    fn keeps_rain_off_the_ground() {
+        let ground = ground();
+        let mut sky = Sky::new(Weather::Rain, 20, 60, 7).unwrap();
+        for _ in 0..50 {
+            sky.tick(&ground);
+            assert!(!sky.drops.is_empty());
+            for drop in &sky.drops {
+                let (y, x) = (drop.y.round() as i32, drop.x.round() as i32);
+                assert!(y < 10 || ground.get(y, x).is_none());
+            }
+        }
+        // rain does not settle
+        assert!(cells(&sky.settled).is_empty());
+    }
+
+    #[test]
+    fn settles_snow_on_what_it_lands_on() {
+        let ground = ground();
+        let mut sky = Sky::new(Weather::Snow, 20, 60, 7).unwrap();
+        for _ in 0..200 {
+            sky.tick(&ground);
+        }
+        let settled = cells(&sky.settled);
+        assert!(!settled.is_empty());
+        // on the ground, or at the bottom for flakes that drifted past its end
+        assert!(settled
+            .iter()
+            .all(|&(y, _, ch)| (y == 9 || y == 19) && ch == '.'));
+        assert!(settled.iter().filter(|&&(y, ..)| y == 9).count() > 50);
+    }
+
+    #[test]
+    fn moves_the_same_way_from_a_seed() {
+        let ground = ground();
+        let mut first = Sky::new(Weather::Snow, 20, 60, 7).unwrap();
+        let mut again = Sky::new(Weather::Snow, 20, 60, 7).unwrap();
+        for _ in 0..30 {
+            first.tick(&ground);
+            again.tick(&ground);
+        }
+        assert_eq!(cells(&first.frame()), cells(&again.frame()));
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context};
+
+use super::canvas::Attr;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl FromStr for Rgb {
+    type Err = anyhow::Error;
+
+    // accepts "#rrggbb" or "rrggbb"
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let hex = s.trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            bail!("expected a color like #1d2021, got {s:?}");
+        }
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16).with_context(|| format!("invalid color {s:?}"))
+        };
+        Ok(Self(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+// the color pairs a tree grows in, and the colors used when it is rendered outside of the
+// terminal
+#[derive(Clone)]
+pub struct Theme {
+    pub tree: TreeColors,
+    pub background: Rgb,
+    pub palette: [Rgb; 16],
+}
+
+impl Theme {
+    // rgb value of the foreground color of a color pair
+    pub fn color(&self, pair: i16) -> Rgb {
+        self.palette[pair.rem_euclid(16) as usize]
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        // xterm's default 16 color palette
+        Self {
+            tree: TreeColors::default(),
+            background: Rgb(0, 0, 0),
+            palette: [
+                Rgb(0, 0, 0),
+                Rgb(205, 0, 0),
+                Rgb(0, 205, 0),
+                Rgb(205, 205, 0),
+                Rgb(0, 0, 238),
+                Rgb(205, 0, 205),
+                Rgb(0, 205, 205),
+                Rgb(229, 229, 229),
+                Rgb(127, 127, 127),
+                Rgb(255, 0, 0),
+                Rgb(0, 255, 0),
+                Rgb(255, 255, 0),
+                Rgb(92, 92, 255),
+                Rgb(255, 0, 255),
+                Rgb(0, 255, 255),
+                Rgb(255, 255, 255),
+            ],
+        }
+    }
+}
+
+// color pairs of the parts of a tree
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TreeColors {
+    // the trunk and shoots take either, half the time each
+    pub wood: (Attr, Attr),
+    // (dying, dead) leaves
+    pub leaves: (i16, i16),
+}
+
+impl Default for TreeColors {
+    fn default() -> Self {
+        Self::seasonal(Season::Summer, false)
+    }
+}
+
+impl TreeColors {
+    // blossoms in spring, green in summer, reds in autumn and snow on bare branches in
+    // winter. night dims everything
+    pub fn seasonal(season: Season, night: bool) -> Self {
+        let (wood, leaves) = match season {
+            Season::Spring => ((Attr::new(11, true), Attr::new(3, false)), (13, 10)),
+            Season::Summer => ((Attr::new(11, true), Attr::new(3, false)), (2, 10)),
+            Season::Autumn => ((Attr::new(11, true), Attr::new(3, false)), (1, 9)),
+            Season::Winter => ((Attr::new(8, true), Attr::new(3, false)), (15, 7)),
+        };
+        if !night {
+            return Self { wood, leaves };
+        }
+        let dim = |attr: Attr| Attr::new(darker(attr.color), false);
+        Self {
+            wood: (dim(wood.0), dim(wood.1)),
+            leaves: (darker(leaves.0), darker(leaves.1)),
+        }
+    }
+
+    // the colors for the local time and date now
+    pub fn now(hemisphere: Hemisphere) -> Self {
+        let (month, hour) = local_time(SystemTime::now()).unwrap_or((6, 12));
+        let season = Season::of_month(month, hemisphere);
+        Self::seasonal(season, !(DAY_HOURS.0..DAY_HOURS.1).contains(&hour))
+    }
+}
+
+// hours of the day, from and up to, a tree is drawn in its daytime colors
+const DAY_HOURS: (i32, i32) = (7, 19);
+
+// the bright colors go to their normal ones, gray stays gray
+fn darker(color: i16) -> i16 {
+    match color {
+        9..=15 => color - 8,
+        _ => color,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl FromStr for Season {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Ok(match value {
+            "spring" => Self::Spring,
+            "summer" => Self::Summer,
+            "autumn" | "fall" => Self::Autumn,
+            "winter" => Self::Winter,
+            _ => bail!("unknown season {value:?}, use spring, summer, autumn or winter"),
+        })
+    }
+}
+
+impl Season {
+    // the season of a month counted from 0 for january, by meteorological seasons
+    pub fn of_month(month: i32, hemisphere: Hemisphere) -> Self {
+        let month = match hemisphere {
+            Hemisphere::North => month,
+            Hemisphere::South => month + 6,
+        };
+        match month.rem_euclid(12) {
+            2..=4 => Self::Spring,
+            5..=7 => Self::Summer,
+            8..=10 => Self::Autumn,
+            _ => Self::Winter,
+        }
+    }
+}
+
+// which half of the world the seasons of --theme auto are for
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Hemisphere {
+    #[default]
+    North,
+    South,
+}
+
+impl FromStr for Hemisphere {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Ok(match value {
+            "north" | "n" => Self::North,
+            "south" | "s" => Self::South,
+            _ => bail!("unknown hemisphere {value:?}, use north or south"),
+        })
+    }
+}
+
+// (month from 0, hour) of a time in the local time zone
+fn local_time(time: SystemTime) -> Option<(i32, i32)> {
+    let secs = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let secs = libc::time_t::try_from(secs).ok()?;
+    let mut tm = unsafe { std::mem::zeroed::<libc::tm>() };
+    if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+        return None;
+    }
+    Some((tm.tm_mon, tm.tm_hour))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_the_seasons_of_each_hemisphere() {
+        use Season::*;
+        let north = [
+            Winter, Winter, Spring, Spring, Spring, Summer, Summer, Summer, Autumn, Autumn, Autumn,
+            Winter,
+        ];
+        for (month, &season) in (0..).zip(&north) {
+            assert_eq!(Season::of_month(month, Hemisphere::North), season);
+        }
+        let south = [
+            (0, Summer),
+            (3, Autumn),
+            (6, Winter),
+            (9, Spring),
+            (11, Summer),
+        ];
+        for (month, season) in south {
+            assert_eq!(Season::of_month(month, Hemisphere::South), season);
+        }
+    }
+
+    #[test]
+    fn dims_the_tree_at_night() {
+        let day = TreeColors::seasonal(Season::Spring, false);
+        let night = TreeColors::seasonal(Season::Spring, true);
+        assert_eq!(day.leaves, (13, 10));
+        assert_eq!(night.leaves, (5, 2));
+        assert_eq!(night.wood.0, Attr::new(3, false));
+        // gray has nothing darker to go to
+        let winter = TreeColors::seasonal(Season::Winter, true);
+        assert_eq!((winter.wood.0.color, winter.leaves), (8, (7, 7)));
+        assert_eq!(
+            TreeColors::default(),
+            TreeColors::seasonal(Season::Summer, false)
+        );
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!("fall".parse::<Season>().unwrap(), Season::Autumn);
+        assert!("monsoon".parse::<Season>().is_err());
+        assert_eq!("s".parse::<Hemisphere>().unwrap(), Hemisphere::South);
+        assert!("east".parse::<Hemisphere>().is_err());
+    }
+
+    #[test]
+    fn reads_and_writes_hex_colors() {
+        assert_eq!("#1d2021".parse::<Rgb>().unwrap(), Rgb(0x1d, 0x20, 0x21));
+        assert_eq!("FFffFF".parse::<Rgb>().unwrap(), Rgb(255, 255, 255));
+        for bad in ["#12345", "#12345g", "#ééé", ""] {
+            assert!(bad.parse::<Rgb>().is_err(), "{bad}");
+        }
+        assert_eq!(Rgb(0, 205, 10).to_string(), "#00cd0a");
+    }
+
+    #[test]
+    fn wraps_color_pairs_onto_the_palette() {
+        let theme = Theme::default();
+        assert_eq!(theme.color(2), Rgb(0, 205, 0));
+        assert_eq!(theme.color(18), theme.color(2));
+        assert_eq!(theme.color(-1), theme.color(15));
+    }
+
+    #[test]
+    fn reads_the_local_clock() {
+        let (month, hour) = local_time(SystemTime::now()).unwrap();
+        assert!((0..12).contains(&month));
+        assert!((0..24).contains(&hour));
+    }
+}
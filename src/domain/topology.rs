@@ -0,0 +1,279 @@
+use super::{branch_type::BranchType, canvas::Attr};
+
+// a string drawn by one step of a branch
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub y: i32,
+    pub x: i32,
+    pub text: String,
+    pub attr: Attr,
+}
+
+impl Segment {
+    fn covers(&self, y: i32, x: i32) -> bool {
+        self.y == y && (self.x..self.x + self.text.chars().count() as i32).contains(&x)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Branch {
+    pub branch_type: BranchType,
+    pub y: i32,
+    pub x: i32,
+    pub life: i32,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub segments: Vec<Segment>,
+}
+
+// every branch grown so far, indexed by id; the trunk is branch 0.
+// children always have a higher id than their parent.
+#[derive(Debug, Default, Clone)]
+pub struct Topology {
+    pub branches: Vec<Branch>,
+    // (branch, segment) in the order they were drawn
+    order: Vec<(usize, usize)>,
+    // branches cut off by pruning, oldest first, and cuts undone since
+    cuts: Vec<usize>,
+    undone: Vec<usize>,
+    removed: Vec<bool>,
+}
+
+impl Topology {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    // register a new branch and return its id
+    pub fn add(
+        &mut self,
+        parent: Option<usize>,
+        branch_type: BranchType,
+        y: i32,
+        x: i32,
+        life: i32,
+    ) -> usize {
+        let id = self.branches.len();
+        if let Some(parent) = parent {
+            self.branches[parent].children.push(id);
+        }
+        self.branches.push(Branch {
+            branch_type,
+            y,
+            x,
+            life,
+            parent,
+            children: Vec::new(),
+            segments: Vec::new(),
+        });
+        self.removed.push(false);
+        id
+    }
+
+    pub fn push_segment(&mut self, id: usize, segment: Segment) {
+        let segments = &mut self.branches[id].segments;
+        self.order.push((id, segments.len()));
+        segments.push(segment);
+    }
+
+    pub fn root(&self) -> Option<&Branch> {
+        self.branches.first()
+    }
+
+    // segments of branches still on the tree, in the order they were drawn
+    pub fn drawn(&self) -> impl Iterator<Item = (usize, &Segment)> {
+        self.order
+            .iter()
+            .filter(|&&(id, _)| !self.removed[id])
+            .map(|&(id, index)| (id, &self.branches[id].segments[index]))
+    }
+
+    // the branch whose visible segment covers (y, x)
+    pub fn branch_at(&self, y: i32, x: i32) -> Option<usize> {
+        self.order
+            .iter()
+            .rev()
+            .filter(|&&(id, _)| !self.removed[id])
+            .find(|&&(id, index)| self.branches[id].segments[index].covers(y, x))
+            .map(|&(id, _)| id)
+    }
+
+    pub fn is_removed(&self, id: usize) -> bool {
+        self.removed[id]
+    }
+
+    // whether id is branch or grew out of it
+    pub fn is_descendant(&self, mut id: usize, branch: usize) -> bool {
+        loop {
+            if id == branch {
+                return true;
+            }
+            match self.branches[id].parent {
+                Some(parent) => id = parent,
+                None => return false,
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.removed.iter().filter(|removed| !**removed).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn cuts(&self) -> &[usize] {
+        &self.cuts
+    }
+
+    // remove a branch and everything growing out of it
+    pub fn cut(&mut self, id: usize) {
+        self.cuts.push(id);
+        self.undone.clear();
+        self.update_removed();
+    }
+
+    // returns false when there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(id) = self.cuts.pop() else {
+            return false;
+        };
+        self.undone.push(id);
+        self.update_removed();
+        true
+    }
+
+    // returns false when there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let Some(id) = self.undone.pop() else {
+            return false;
+        };
+        self.cuts.push(id);
+        self.update_removed();
+        true
+    }
+
+    // reapply saved cuts, ignoring branches that never grew
+    pub fn set_cuts(&mut self, cuts: &[usize]) {
+        self.cuts = cuts
+            .iter()
+            .copied()
+            .filter(|&id| id < self.branches.len())
+            .collect();
+        self.undone.clear();
+        self.update_removed();
+    }
+
+    fn update_removed(&mut self) {
+        for id in 0..self.branches.len() {
+            let parent_removed = self.branches[id]
+                .parent
+                .is_some_and(|parent| self.removed[parent]);
+            self.removed[id] = parent_removed || self.cuts.contains(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a trunk (0) with a branch (1) that has a leaf (2), and a second leaf (3) on the trunk
+    fn topology() -> Topology {
+        let mut topology = Topology::default();
+        let trunk = topology.add(None, BranchType::Trunk, 9, 5, 10);
+        let branch = topology.add(Some(trunk), BranchType::ShootLeft, 8, 5, 6);
+        topology.add(Some(branch), BranchType::Dead, 7, 4, 2);
+        topology.add(Some(trunk), BranchType::Dead, 8, 6, 2);
+        topology
+    }
+
+    fn removed(topology: &Topology) -> Vec<bool> {
+        (0..topology.branches.len())
+            .map(|id| topology.is_removed(id))
+            .collect()
+    }
+
+    #[test]
+    fn cut_removes_what_grew_out_of_a_branch() {
+        let mut topology = topology();
+        topology.cut(1);
+        assert_eq!(removed(&topology), [false, true, true, false]);
+        assert_eq!(topology.len(), 2);
+        assert_eq!(topology.cuts(), [1]);
+    }
+
+    #[test]
+    fn undo_and_redo_round_trip() {
+        let mut topology = topology();
+        assert!(!topology.undo());
+        assert!(!topology.redo());
+
+        topology.cut(1);
+        topology.cut(3);
+        assert_eq!(removed(&topology), [false, true, true, true]);
+
+        assert!(topology.undo());
+        assert_eq!(removed(&topology), [false, true, true, false]);
+        assert!(topology.undo());
+        assert_eq!(removed(&topology), [false; 4]);
+        assert!(topology.cuts().is_empty());
+        assert!(!topology.undo());
+
+        assert!(topology.redo());
+        assert!(topology.redo());
+        assert_eq!(removed(&topology), [false, true, true, true]);
+        assert_eq!(topology.cuts(), [1, 3]);
+        assert!(!topology.redo());
+    }
+
+    #[test]
+    fn a_new_cut_drops_the_undone_ones() {
+        let mut topology = topology();
+        topology.cut(1);
+        assert!(topology.undo());
+        topology.cut(3);
+        assert!(!topology.redo());
+        assert_eq!(topology.cuts(), [3]);
+        assert_eq!(removed(&topology), [false, false, false, true]);
+    }
+
+    #[test]
+    fn set_cuts_ignores_branches_that_never_grew() {
+        let mut topology = topology();
+        topology.cut(3);
+        assert!(topology.undo());
+        topology.set_cuts(&[1, 99]);
+        assert_eq!(topology.cuts(), [1]);
+        assert_eq!(removed(&topology), [false, true, true, false]);
+        // nothing left to redo once cuts are replaced
+        assert!(!topology.redo());
+        assert!(topology.undo());
+        assert_eq!(removed(&topology), [false; 4]);
+    }
+
+    #[test]
+    fn cut_branches_are_not_drawn_or_found() {
+        let mut topology = topology();
+        let attr = Attr::new(2, false);
+        for (id, x) in [(0, 5), (1, 3), (2, 3)] {
+            let text = "&&".to_owned();
+            topology.push_segment(
+                id,
+                Segment {
+                    y: 4,
+                    x,
+                    text,
+                    attr,
+                },
+            );
+        }
+        assert_eq!(topology.branch_at(4, 4), Some(2));
+        topology.cut(1);
+        assert_eq!(topology.branch_at(4, 4), None);
+        assert_eq!(topology.branch_at(4, 6), Some(0));
+        let drawn: Vec<usize> = topology.drawn().map(|(id, _)| id).collect();
+        assert_eq!(drawn, [0]);
+    }
+}
@@ -0,0 +1,245 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+
+use super::{
+    canvas::{Attr, Canvas},
+    garden,
+};
+
+// how a tree leaves the screen before the next one grows in infinite mode
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Transition {
+    // gone at once, like it always was
+    #[default]
+    Cut,
+    // leaves drop to the ground while the trunk fades
+    Fall,
+    // cells blink out one by one
+    Dissolve,
+    // swept away from the left
+    Wipe,
+    // leaves yellow and brown, drop off, then the trunk goes
+    Wither,
+}
+
+impl FromStr for Transition {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Ok(match value {
+            "cut" | "none" => Self::Cut,
+            "fall" => Self::Fall,
+            "dissolve" => Self::Dissolve,
+            "wipe" => Self::Wipe,
+            "wither" => Self::Wither,
+            _ => bail!("unknown transition {value:?}, use cut, fall, dissolve, wipe or wither"),
+        })
+    }
+}
+
+const FADED: Attr = Attr::new(8, false);
+
+impl Transition {
+    // the tree part way through leaving, progress runs from 0 to 1. leaves holds the cells of
+    // the tree that are leaves, seed picks the order cells go in
+    pub fn frame(self, tree: &Canvas, leaves: &Canvas, progress: f64, seed: u64) -> Canvas {
+        let mut frame = Canvas::new(tree.width, tree.height);
+        for y in 0..tree.height {
+            for x in 0..tree.width {
+                let Some(mut cell) = tree.get(y, x) else {
+                    continue;
+                };
+                let noise = noise(seed, y, x);
+                let leaf = leaves.get(y, x).is_some();
+                let mut to = (y, x);
+                match self {
+                    Self::Cut => continue,
+                    Self::Fall if leaf => {
+                        // leaves let go one after another and speed up as they fall
+                        let falling = (progress - noise * 0.5).max(0.0) * 2.0;
+                        to.0 += (falling * falling * f64::from(tree.height)) as i32;
+                    }
+                    Self::Fall => {
+                        if progress > 0.7 + noise * 0.3 {
+                            continue;
+                        }
+                        if progress > 0.4 {
+                            cell.attr = FADED;
+                        }
+                    }
+                    Self::Dissolve => {
+                        if noise < progress {
+                            continue;
+                        }
+                    }
+                    Self::Wipe => {
+                        if f64::from(x) < progress * f64::from(tree.width) {
+                            continue;
+                        }
+                    }
+                    Self::Wither if leaf => {
+                        let (yellow, brown) = garden::THIRSTY_LEAVES;
+                        let (gray, dead) = garden::WILTED_LEAVES;
+                        if progress > 0.6 + noise * 0.3 {
+                            continue;
+                        }
+                        if progress > 0.3 + noise * 0.3 {
+                            cell.attr = Attr::new(if noise < 0.5 { gray } else { dead }, false);
+                        } else if progress > noise * 0.3 {
+                            cell.attr = Attr::new(if noise < 0.5 { yellow } else { brown }, false);
+                        }
+                    }
+                    Self::Wither => {
+                        if progress > 0.9 + noise * 0.1 {
+                            continue;
+                        }
+                        if progress > 0.7 {
+                            cell.attr = FADED;
+                        }
+                    }
+                }
+                // a fallen leaf lands on whatever is already there
+                if frame.get(to.0, to.1).is_none() {
+                    frame.set(to.0, to.1, Some(cell));
+                }
+            }
+        }
+        frame
+    }
+}
+
+// a number in [0, 1) that stays the same for a cell, splitmix64 of its position
+fn noise(seed: u64, y: i32, x: i32) -> f64 {
+    let mut z = seed ^ ((y as u64) << 32 | x as u32 as u64);
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Transition; 5] = [
+        Transition::Cut,
+        Transition::Fall,
+        Transition::Dissolve,
+        Transition::Wipe,
+        Transition::Wither,
+    ];
+
+    // a trunk up the middle column, leaves across the top row
+    fn tree() -> (Canvas, Canvas) {
+        let mut tree = Canvas::new(10, 6);
+        let mut leaves = Canvas::new(10, 6);
+        for y in 1..6 {
+            tree.put_str(y, 5, "|", Attr::new(3, true));
+        }
+        tree.put_str(0, 0, "&&&&&&&&&&", Attr::new(2, false));
+        leaves.put_str(0, 0, "&&&&&&&&&&", Attr::new(2, false));
+        (tree, leaves)
+    }
+
+    fn filled(canvas: &Canvas) -> Vec<(i32, i32)> {
+        (0..canvas.height)
+            .flat_map(|y| (0..canvas.width).map(move |x| (y, x)))
+            .filter(|&(y, x)| canvas.get(y, x).is_some())
+            .collect()
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!("wither".parse::<Transition>().unwrap(), Transition::Wither);
+        assert_eq!("none".parse::<Transition>().unwrap(), Transition::Cut);
+        assert!("fade".parse::<Transition>().is_err());
+    }
+
+    #[test]
+    fn starts_with_the_tree_and_ends_empty() {
+        let (tree, leaves) = tree();
+        for transition in ALL {
+            let start = transition.frame(&tree, &leaves, 0.0, 7);
+            let end = transition.frame(&tree, &leaves, 1.0, 7);
+            assert!(filled(&end).is_empty(), "{transition:?}");
+            if transition == Transition::Cut {
+                assert!(filled(&start).is_empty());
+                continue;
+            }
+            for (y, x) in filled(&tree) {
+                assert_eq!(start.get(y, x), tree.get(y, x), "{transition:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn wipes_from_the_left() {
+        let (tree, leaves) = tree();
+        let frame = Transition::Wipe.frame(&tree, &leaves, 0.5, 7);
+        assert!(filled(&frame).iter().all(|&(_, x)| x >= 5));
+        assert_eq!(frame.get(3, 5), tree.get(3, 5));
+    }
+
+    #[test]
+    fn dissolves_cells_for_good() {
+        let (tree, leaves) = tree();
+        let mut left = filled(&tree).len();
+        for step in 1..=10 {
+            let progress = f64::from(step) / 10.0;
+            let frame = Transition::Dissolve.frame(&tree, &leaves, progress, 7);
+            let cells = filled(&frame);
+            assert!(cells.len() <= left);
+            left = cells.len();
+        }
+        // another seed dissolves in another order
+        let (a, b) = (
+            Transition::Dissolve.frame(&tree, &leaves, 0.5, 7),
+            Transition::Dissolve.frame(&tree, &leaves, 0.5, 8),
+        );
+        assert_ne!(filled(&a), filled(&b));
+    }
+
+    #[test]
+    fn drops_leaves_and_fades_the_trunk() {
+        let (tree, leaves) = tree();
+        let frame = Transition::Fall.frame(&tree, &leaves, 0.5, 7);
+        // the trunk stays put, faded
+        assert_eq!(frame.get(3, 5).unwrap().attr, FADED);
+        // halfway through, some leaves have let go
+        let fallen = filled(&frame)
+            .into_iter()
+            .filter(|&(y, x)| frame.get(y, x).unwrap().ch == '&')
+            .collect::<Vec<_>>();
+        assert!(fallen.iter().any(|&(y, _)| y > 0));
+    }
+
+    #[test]
+    fn withers_leaves_before_the_trunk() {
+        let (tree, leaves) = tree();
+        let frame = Transition::Wither.frame(&tree, &leaves, 0.5, 7);
+        let colors = [
+            garden::THIRSTY_LEAVES.0,
+            garden::THIRSTY_LEAVES.1,
+            garden::WILTED_LEAVES.0,
+            garden::WILTED_LEAVES.1,
+        ];
+        for x in 0..10 {
+            if let Some(cell) = frame.get(0, x) {
+                assert!(colors.contains(&cell.attr.color));
+            }
+        }
+        assert_eq!(frame.get(3, 5), tree.get(3, 5));
+    }
+
+    #[test]
+    fn noise_is_stable_and_in_range() {
+        for (y, x) in [(0, 0), (5, -3), (-7, 1000)] {
+            let value = noise(42, y, x);
+            assert!((0.0..1.0).contains(&value));
+            assert_eq!(value, noise(42, y, x));
+        }
+        assert_ne!(noise(42, 1, 2), noise(42, 2, 1));
+    }
+}
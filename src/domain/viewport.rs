@@ -0,0 +1,157 @@
+use super::canvas::Canvas;
+
+// the part of a canvas bigger than the screen that is on screen. y and x are how far the view
+// has moved from home, where the screen was before any panning, in cells of the canvas
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    pub y: i32,
+    pub x: i32,
+    // canvas cells per screen cell across and down
+    pub zoom: i32,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            y: 0,
+            x: 0,
+            zoom: 1,
+        }
+    }
+}
+
+impl Viewport {
+    pub fn is_home(&self) -> bool {
+        self.y == 0 && self.x == 0 && self.zoom == 1
+    }
+
+    // move by rows and cols of screen cells
+    pub fn pan(&mut self, rows: i32, cols: i32) {
+        self.y += rows * self.zoom;
+        self.x += cols * self.zoom;
+    }
+
+    // zoom out by steps, in when negative, keeping the middle of a rows x cols screen in place
+    pub fn zoom(&mut self, steps: i32, rows: i32, cols: i32) {
+        let (middle_y, middle_x) = (self.y + rows * self.zoom / 2, self.x + cols * self.zoom / 2);
+        self.zoom = (self.zoom + steps).max(1);
+        self.y = middle_y - rows * self.zoom / 2;
+        self.x = middle_x - cols * self.zoom / 2;
+    }
+
+    // keep a rows x cols screen over the world, zoomed out no further than it takes to see all
+    // of it. home is where the corner of the screen is on the world
+    pub fn clamp(&mut self, world: &Canvas, home: (i32, i32), rows: i32, cols: i32) {
+        let whole = div_ceil(world.height, rows).max(div_ceil(world.width, cols));
+        self.zoom = self.zoom.clamp(1, whole.max(1));
+        let bottom = (world.height - rows * self.zoom).max(0);
+        let right = (world.width - cols * self.zoom).max(0);
+        self.y = (home.0 + self.y).clamp(0, bottom) - home.0;
+        self.x = (home.1 + self.x).clamp(0, right) - home.1;
+    }
+
+    // the rows x cols screen of the world seen through the view. zoomed out, a screen cell
+    // shows the first filled cell of the block of the world under it
+    pub fn render(&self, world: &Canvas, home: (i32, i32), rows: i32, cols: i32) -> Canvas {
+        let (top, left) = (home.0 + self.y, home.1 + self.x);
+        let mut screen = Canvas::new(cols, rows);
+        for y in 0..rows {
+            for x in 0..cols {
+                let cell = (0..self.zoom)
+                    .flat_map(|dy| (0..self.zoom).map(move |dx| (dy, dx)))
+                    .find_map(|(dy, dx)| {
+                        world.get(top + y * self.zoom + dy, left + x * self.zoom + dx)
+                    });
+                screen.set(y, x, cell);
+            }
+        }
+        screen
+    }
+}
+
+fn div_ceil(value: i32, by: i32) -> i32 {
+    (value + by - 1) / by.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::canvas::Attr;
+
+    const GREEN: Attr = Attr::new(2, false);
+
+    fn at(y: i32, x: i32, zoom: i32) -> Viewport {
+        Viewport { y, x, zoom }
+    }
+
+    fn chars(canvas: &Canvas, y: i32) -> String {
+        let row = canvas.row(y).iter();
+        row.map(|cell| cell.map_or('.', |cell| cell.ch)).collect()
+    }
+
+    #[test]
+    fn pans_in_screen_cells() {
+        let mut view = Viewport::default();
+        assert!(view.is_home());
+        view.pan(2, -4);
+        assert_eq!((view.y, view.x), (2, -4));
+        assert!(!view.is_home());
+        let mut zoomed = at(0, 0, 3);
+        zoomed.pan(1, 1);
+        assert_eq!((zoomed.y, zoomed.x), (3, 3));
+    }
+
+    #[test]
+    fn zooms_around_the_middle() {
+        let mut view = Viewport::default();
+        view.zoom(1, 10, 20);
+        assert_eq!((view.y, view.x, view.zoom), (-5, -10, 2));
+        view.zoom(-1, 10, 20);
+        assert!(view.is_home());
+        view.zoom(-1, 10, 20);
+        assert!(view.is_home());
+    }
+
+    #[test]
+    fn stays_over_the_world() {
+        let world = Canvas::new(40, 30);
+        let home = (10, 5);
+        // no further out than it takes to see all of it
+        let mut view = at(0, 0, 5);
+        view.clamp(&world, home, 10, 20);
+        assert_eq!(view.zoom, 3);
+        let mut view = at(100, -100, 1);
+        view.clamp(&world, home, 10, 20);
+        assert_eq!((view.y, view.x), (10, -5));
+        // a world smaller than the screen is seen from its corner
+        let mut view = at(3, 3, 1);
+        view.clamp(&Canvas::new(5, 5), (0, 0), 10, 20);
+        assert_eq!((view.y, view.x, view.zoom), (0, 0, 1));
+    }
+
+    #[test]
+    fn shows_the_world_through_the_view() {
+        let mut world = Canvas::new(8, 4);
+        world.put_str(0, 0, "abcdefgh", GREEN);
+        world.put_str(1, 1, "x", GREEN);
+        world.put_str(3, 6, "yz", GREEN);
+
+        let screen = at(1, 2, 1).render(&world, (0, 1), 2, 4);
+        assert_eq!(
+            (chars(&screen, 0), chars(&screen, 1)),
+            ("....".into(), "....".into())
+        );
+        let screen = at(0, 0, 1).render(&world, (0, 1), 2, 4);
+        assert_eq!(
+            (chars(&screen, 0), chars(&screen, 1)),
+            ("bcde".into(), "x...".into())
+        );
+
+        // zoomed out, the first filled cell of each block shows
+        let screen = at(0, 0, 2).render(&world, (0, 0), 2, 4);
+        assert_eq!(
+            (chars(&screen, 0), chars(&screen, 1)),
+            ("aceg".into(), "...y".into())
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use crate::domain::canvas::{Attr, Canvas};
+
+pub const RESET: &str = "\x1b[0m";
+
+// select graphic rendition sequence for a color pair, resetting whatever came before
+pub fn sgr(attr: Attr) -> String {
+    let color = attr.color.rem_euclid(16);
+    let code = if color < 8 {
+        30 + color
+    } else {
+        90 + color - 8
+    };
+    if attr.bold {
+        format!("\x1b[0;1;{code}m")
+    } else {
+        format!("\x1b[0;{code}m")
+    }
+}
+
+// move the cursor to a zero based (y, x)
+pub fn move_to(y: i32, x: i32) -> String {
+    format!("\x1b[{};{}H", y + 1, x + 1)
+}
+
+// the canvas as lines of colored text, trailing blanks trimmed
+pub fn render(canvas: &Canvas) -> String {
+    render_with(canvas, true)
+}
+
+// the canvas as lines of text without colors
+pub fn render_plain(canvas: &Canvas) -> String {
+    render_with(canvas, false)
+}
+
+fn render_with(canvas: &Canvas, color: bool) -> String {
+    let mut text = String::new();
+    for y in 0..canvas.height {
+        let row = canvas.row(y);
+        let end = row.iter().rposition(Option::is_some).map_or(0, |x| x + 1);
+        let mut current = None;
+        for cell in &row[..end] {
+            match cell {
+                Some(cell) => {
+                    if color && current != Some(cell.attr) {
+                        text += &sgr(cell.attr);
+                        current = Some(cell.attr);
+                    }
+                    text.push(cell.ch);
+                }
+                None => text.push(' '),
+            }
+        }
+        if current.is_some() {
+            text += RESET;
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canvas() -> Canvas {
+        let mut canvas = Canvas::new(10, 3);
+        canvas.put_str(1, 1, "ab", Attr::new(2, false));
+        canvas.put_str(1, 4, "c", Attr::new(11, true));
+        canvas
+    }
+
+    #[test]
+    fn picks_normal_and_bright_colors() {
+        assert_eq!(sgr(Attr::new(2, false)), "\x1b[0;32m");
+        assert_eq!(sgr(Attr::new(11, true)), "\x1b[0;1;93m");
+        // pairs past 16 wrap around like the curses palette
+        assert_eq!(sgr(Attr::new(17, false)), "\x1b[0;31m");
+        assert_eq!(move_to(0, 0), "\x1b[1;1H");
+        assert_eq!(move_to(4, 9), "\x1b[5;10H");
+    }
+
+    #[test]
+    fn renders_plain_lines_without_trailing_blanks() {
+        assert_eq!(render_plain(&canvas()), "\n ab c\n\n");
+    }
+
+    #[test]
+    fn renders_colors_and_resets_each_line() {
+        assert_eq!(
+            render(&canvas()),
+            format!("\n \x1b[0;32mab \x1b[0;1;93mc{RESET}\n\n")
+        );
+    }
+}
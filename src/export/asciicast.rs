@@ -0,0 +1,185 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use serde_json::json;
+
+use crate::domain::canvas::{Attr, Canvas};
+
+use super::ansi;
+
+// records every frame of growth as an asciicast v2 stream
+pub struct Recorder {
+    events: String,
+    previous: Canvas,
+    width: i32,
+    height: i32,
+    start: Instant,
+    frames: u32,
+    // without a terminal frames are spaced time_step apart instead of by the clock
+    realtime: bool,
+    time_step: Duration,
+}
+
+impl Recorder {
+    pub fn new(width: i32, height: i32, time_step: u64, realtime: bool) -> Self {
+        Self {
+            events: String::new(),
+            previous: Canvas::new(width, height),
+            width,
+            height,
+            start: Instant::now(),
+            frames: 0,
+            realtime,
+            time_step: Duration::from_millis(time_step),
+        }
+    }
+
+    // emit whatever changed on the canvas since the last frame
+    pub fn frame(&mut self, canvas: &Canvas) {
+        let mut output = String::new();
+        if self.frames == 0 {
+            output.push_str("\x1b[2J\x1b[H");
+        }
+
+        let mut cursor = None;
+        let mut current = None;
+        for y in 0..canvas.height.min(self.height) {
+            for x in 0..canvas.width.min(self.width) {
+                let cell = canvas.get(y, x);
+                if cell == self.previous.get(y, x) {
+                    continue;
+                }
+                if cursor != Some((y, x)) {
+                    output.push_str(&ansi::move_to(y, x));
+                }
+                let (ch, attr) = cell.map_or((' ', Attr::default()), |cell| (cell.ch, cell.attr));
+                if current != Some(attr) {
+                    output.push_str(&ansi::sgr(attr));
+                    current = Some(attr);
+                }
+                output.push(ch);
+                cursor = Some((y, x + 1));
+                self.previous.set(y, x, cell);
+            }
+        }
+
+        let time = if self.realtime {
+            self.start.elapsed()
+        } else {
+            self.time_step * self.frames
+        };
+        self.frames += 1;
+
+        if !output.is_empty() {
+            let event = json!([time.as_secs_f64(), "o", output]);
+            let _ = writeln!(self.events, "{event}");
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let header = json!({
+            "version": 2,
+            "width": self.width,
+            "height": self.height,
+            "timestamp": timestamp,
+            "env": { "TERM": "xterm-256color" },
+        });
+        fs::write(path, format!("{header}\n{}", self.events))
+            .with_context(|| format!("couldnt write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    const GREEN: Attr = Attr::new(2, false);
+
+    fn events(recorder: &Recorder) -> Vec<Value> {
+        recorder
+            .events
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn writes_only_what_changed() {
+        let mut recorder = Recorder::new(10, 3, 50, false);
+        let mut canvas = Canvas::new(10, 3);
+        canvas.put_str(1, 2, "ab", GREEN);
+        recorder.frame(&canvas);
+        // nothing changed, no event
+        recorder.frame(&canvas);
+        canvas.put_str(1, 3, "c", GREEN);
+        canvas.put_str(2, 0, "d", GREEN);
+        canvas.set(1, 2, None);
+        recorder.frame(&canvas);
+
+        let events = events(&recorder);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0][1], "o");
+        assert_eq!(events[0][2], "\x1b[2J\x1b[H\x1b[2;3H\x1b[0;32mab");
+        // an emptied cell is blanked
+        assert_eq!(events[1][2], "\x1b[2;3H\x1b[0;30m \x1b[0;32mc\x1b[3;1Hd");
+    }
+
+    #[test]
+    fn spaces_frames_by_the_time_step_off_screen() {
+        let mut recorder = Recorder::new(4, 1, 250, false);
+        let mut canvas = Canvas::new(4, 1);
+        for (x, ch) in ["a", "b", "c"].into_iter().enumerate() {
+            canvas.put_str(0, x as i32, ch, GREEN);
+            recorder.frame(&canvas);
+        }
+        let times: Vec<f64> = events(&recorder)
+            .iter()
+            .map(|event| event[0].as_f64().unwrap())
+            .collect();
+        assert_eq!(times, [0.0, 0.25, 0.5]);
+    }
+
+    #[test]
+    fn leaves_out_cells_past_the_recording() {
+        let mut recorder = Recorder::new(2, 1, 50, false);
+        let mut canvas = Canvas::new(4, 2);
+        canvas.put_str(0, 0, "abcd", GREEN);
+        canvas.put_str(1, 0, "e", GREEN);
+        recorder.frame(&canvas);
+        assert_eq!(
+            events(&recorder)[0][2],
+            "\x1b[2J\x1b[H\x1b[1;1H\x1b[0;32mab"
+        );
+    }
+
+    #[test]
+    fn saves_a_header_then_the_events() {
+        let mut recorder = Recorder::new(4, 2, 50, false);
+        let mut canvas = Canvas::new(4, 2);
+        canvas.put_str(0, 0, "a", GREEN);
+        recorder.frame(&canvas);
+        let path = std::env::temp_dir().join(format!("bonsai-cast-{}.cast", std::process::id()));
+        recorder.save(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut lines = text.lines();
+        let header: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(header["version"], 2);
+        assert_eq!(
+            (&header["width"], &header["height"]),
+            (&Value::from(4), &Value::from(2))
+        );
+        assert_eq!(lines.count(), 1);
+    }
+}
@@ -0,0 +1,220 @@
+use std::{borrow::Cow, fs::File, io::BufWriter, path::Path};
+
+use anyhow::{bail, Context};
+
+use crate::domain::{canvas::Canvas, config::Config};
+
+use super::raster::Raster;
+
+// snapshots of the canvas taken every few steps of growth
+pub struct Animation {
+    frames: Vec<Canvas>,
+    batch: u32,
+    steps: u32,
+}
+
+impl Animation {
+    pub fn new(batch: u32) -> Self {
+        Self {
+            frames: Vec::new(),
+            batch: batch.max(1),
+            steps: 0,
+        }
+    }
+
+    pub fn frame(&mut self, canvas: &Canvas) {
+        if self.steps.is_multiple_of(self.batch) {
+            self.frames.push(canvas.clone());
+        }
+        self.steps += 1;
+    }
+}
+
+pub fn export(
+    canvas: &Canvas,
+    animation: Option<&Animation>,
+    config: &Config,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let width = (canvas.width as u32)
+        .checked_mul(config.cell_width)
+        .and_then(|width| u16::try_from(width).ok());
+    let height = (canvas.height as u32)
+        .checked_mul(config.cell_height)
+        .and_then(|height| u16::try_from(height).ok());
+    let (Some(width), Some(height)) = (width, height) else {
+        bail!("canvas is too large for a gif, try a smaller --size or --cell-size");
+    };
+
+    let file = File::create(path).with_context(|| format!("couldnt create {}", path.display()))?;
+    let palette = Raster::palette(&config.theme);
+    let mut encoder = gif::Encoder::new(BufWriter::new(file), width, height, &palette)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    // gif delays are in hundredths of a second
+    let batch = animation.map_or(1, |animation| animation.batch);
+    let step_delay = config.time_step * u64::from(batch) / 10;
+    let hold_delay = (config.gif_hold * 100.0) as u64;
+
+    // each frame only holds the cells that changed since the one before, drawn over what it
+    // kept. a frame changing nothing adds its delay to the previous one instead
+    let frames = animation.map_or(&[][..], |animation| &animation.frames[..]);
+    let mut previous: Option<&Canvas> = None;
+    let mut pending: Option<(gif::Frame, u64)> = None;
+    for (frame, delay) in frames
+        .iter()
+        .map(|frame| (frame, step_delay))
+        .chain(Some((canvas, hold_delay)))
+    {
+        let Some((top, left, bottom, right)) = changed(previous, frame) else {
+            if let Some((_, held)) = &mut pending {
+                *held += delay;
+            }
+            continue;
+        };
+        if let Some((frame, delay)) = pending.take() {
+            write_frame(&mut encoder, frame, delay)?;
+        }
+
+        let mut part = Canvas::new(right - left + 1, bottom - top + 1);
+        part.paste(-top, -left, frame);
+        let raster = Raster::new(&part, config.cell_width, config.cell_height)?;
+        let gif_frame = gif::Frame {
+            left: (left as u32 * config.cell_width) as u16,
+            top: (top as u32 * config.cell_height) as u16,
+            width: raster.width as u16,
+            height: raster.height as u16,
+            dispose: gif::DisposalMethod::Keep,
+            buffer: Cow::Owned(raster.pixels),
+            ..gif::Frame::default()
+        };
+        pending = Some((gif_frame, delay));
+        previous = Some(frame);
+    }
+    if let Some((frame, delay)) = pending {
+        write_frame(&mut encoder, frame, delay)?;
+    }
+    Ok(())
+}
+
+fn write_frame(
+    encoder: &mut gif::Encoder<BufWriter<File>>,
+    mut frame: gif::Frame,
+    delay: u64,
+) -> anyhow::Result<()> {
+    frame.delay = delay.min(u64::from(u16::MAX)) as u16;
+    encoder.write_frame(&frame)?;
+    Ok(())
+}
+
+// (top, left, bottom, right) of the cells that differ between two frames, all of it when
+// there is no frame before. none when nothing changed
+fn changed(previous: Option<&Canvas>, canvas: &Canvas) -> Option<(i32, i32, i32, i32)> {
+    let full = (0, 0, canvas.height - 1, canvas.width - 1);
+    let Some(previous) = previous else {
+        return Some(full);
+    };
+    if (previous.width, previous.height) != (canvas.width, canvas.height) {
+        return Some(full);
+    }
+    let mut bounds: Option<(i32, i32, i32, i32)> = None;
+    for y in 0..canvas.height {
+        for x in 0..canvas.width {
+            if previous.get(y, x) != canvas.get(y, x) {
+                let (top, left, bottom, right) = bounds.unwrap_or((y, x, y, x));
+                bounds = Some((top.min(y), left.min(x), bottom.max(y), right.max(x)));
+            }
+        }
+    }
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::canvas::Attr;
+
+    const GREEN: Attr = Attr::new(2, false);
+
+    #[test]
+    fn finds_the_cells_that_changed() {
+        let empty = Canvas::new(10, 5);
+        let mut grown = empty.clone();
+        assert_eq!(changed(None, &empty), Some((0, 0, 4, 9)));
+        assert_eq!(changed(Some(&empty), &grown), None);
+
+        grown.put_str(1, 6, "ab", GREEN);
+        grown.put_str(3, 2, "c", GREEN);
+        assert_eq!(changed(Some(&empty), &grown), Some((1, 2, 3, 7)));
+        // a new color in the same place counts too
+        let mut recolored = grown.clone();
+        recolored.put_str(3, 2, "c", Attr::new(2, true));
+        assert_eq!(changed(Some(&grown), &recolored), Some((3, 2, 3, 2)));
+        // a canvas of another size is drawn whole
+        assert_eq!(
+            changed(Some(&empty), &Canvas::new(4, 2)),
+            Some((0, 0, 1, 3))
+        );
+    }
+
+    #[test]
+    fn keeps_a_frame_every_batch_of_steps() {
+        let mut animation = Animation::new(3);
+        for _ in 0..7 {
+            animation.frame(&Canvas::new(1, 1));
+        }
+        // steps 0, 3 and 6
+        assert_eq!(animation.frames.len(), 3);
+        assert_eq!(Animation::new(0).batch, 1);
+    }
+
+    #[test]
+    fn writes_changed_parts_and_merges_still_frames() {
+        let config = Config {
+            cell_width: 2,
+            cell_height: 3,
+            time_step: 40,
+            gif_hold: 2.0,
+            ..Config::default()
+        };
+        let mut canvas = Canvas::new(8, 4);
+        let mut animation = Animation::new(1);
+        animation.frame(&canvas);
+        canvas.put_str(2, 3, "ab", GREEN);
+        animation.frame(&canvas);
+        // nothing grew this step, its delay goes to the frame before
+        animation.frame(&canvas);
+
+        let path = std::env::temp_dir().join(format!("bonsai-gif-{}.gif", std::process::id()));
+        export(&canvas, Some(&animation), &config, &path).unwrap();
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(File::open(&path).unwrap())
+            .unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (16, 12));
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((
+                frame.left,
+                frame.top,
+                frame.width,
+                frame.height,
+                frame.delay,
+            ));
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        // the finished tree is the last frame grown, held for --gif-hold
+        assert_eq!(frames, [(0, 0, 16, 12, 4), (6, 6, 4, 3, 4 + 4 + 200)]);
+    }
+
+    #[test]
+    fn refuses_a_canvas_too_large_for_a_gif() {
+        let config = Config {
+            cell_width: 10_000,
+            ..Config::default()
+        };
+        let path = std::env::temp_dir().join("bonsai-never-written.gif");
+        assert!(export(&Canvas::new(80, 24), None, &config, &path).is_err());
+        assert!(!path.exists());
+    }
+}
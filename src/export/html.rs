@@ -0,0 +1,155 @@
+use std::{fmt::Write as _, fs, path::Path};
+
+use anyhow::Context;
+
+use crate::domain::{
+    canvas::{Attr, Canvas},
+    config::Config,
+    theme::Theme,
+};
+
+pub fn export(canvas: &Canvas, config: &Config, path: &Path) -> anyhow::Result<()> {
+    fs::write(path, document(canvas, config))
+        .with_context(|| format!("couldnt write {}", path.display()))
+}
+
+// a snippet, or a whole page with --html-page
+pub fn document(canvas: &Canvas, config: &Config) -> String {
+    let snippet = render(canvas, &config.theme);
+    if config.html_page {
+        page(&snippet, &config.theme)
+    } else {
+        snippet
+    }
+}
+
+// a <pre> with one <span> per run of cells sharing the same attributes
+fn render(canvas: &Canvas, theme: &Theme) -> String {
+    let mut html = String::from("<pre class=\"bonsai\">");
+    for y in 0..canvas.height {
+        let row = canvas.row(y);
+        // trailing empty cells would only pad the line with spaces
+        let len = row.iter().rposition(Option::is_some).map_or(0, |i| i + 1);
+
+        let mut run: Option<Attr> = None;
+        for cell in &row[..len] {
+            let (ch, attr) = match cell {
+                Some(cell) if cell.ch != ' ' => (cell.ch, Some(cell.attr)),
+                _ => (' ', run),
+            };
+            if attr != run {
+                if run.is_some() {
+                    html.push_str("</span>");
+                }
+                if let Some(attr) = attr {
+                    open_span(&mut html, attr, theme);
+                }
+                run = attr;
+            }
+            push_escaped(&mut html, ch);
+        }
+        if run.is_some() {
+            html.push_str("</span>");
+        }
+        html.push('\n');
+    }
+    html.push_str("</pre>\n");
+    html
+}
+
+fn page(snippet: &str, theme: &Theme) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>bonsai</title>\n\
+         <style>body {{ background: {}; color: {}; }}</style>\n</head>\n<body>\n{snippet}</body>\n</html>\n",
+        theme.background,
+        theme.color(7),
+    )
+}
+
+fn open_span(html: &mut String, attr: Attr, theme: &Theme) {
+    let _ = write!(html, "<span style=\"color:{}", theme.color(attr.color));
+    if attr.bold {
+        html.push_str(";font-weight:bold");
+    }
+    html.push_str("\">");
+}
+
+fn push_escaped(html: &mut String, ch: char) {
+    match ch {
+        '&' => html.push_str("&amp;"),
+        '<' => html.push_str("&lt;"),
+        '>' => html.push_str("&gt;"),
+        '"' => html.push_str("&quot;"),
+        _ => html.push(ch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GREEN: Attr = Attr::new(2, false);
+    const RED: Attr = Attr::new(1, false);
+
+    fn one_row(pieces: &[(i32, &str, Attr)]) -> String {
+        let mut canvas = Canvas::new(20, 1);
+        for &(x, text, attr) in pieces {
+            canvas.put_str(0, x, text, attr);
+        }
+        render(&canvas, &Theme::default())
+    }
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(
+            one_row(&[(0, "<&>\"'", GREEN)]),
+            "<pre class=\"bonsai\"><span style=\"color:#00cd00\">&lt;&amp;&gt;&quot;'</span>\n</pre>\n"
+        );
+    }
+
+    #[test]
+    fn merges_runs_of_the_same_attributes() {
+        // the gap between two runs of one color stays in their span
+        assert_eq!(
+            one_row(&[(1, "ab", GREEN), (4, "cd", GREEN)]),
+            "<pre class=\"bonsai\"> <span style=\"color:#00cd00\">ab cd</span>\n</pre>\n"
+        );
+        assert_eq!(
+            one_row(&[
+                (0, "ab", GREEN),
+                (2, "cd", RED),
+                (4, "e", Attr::new(1, true))
+            ]),
+            "<pre class=\"bonsai\"><span style=\"color:#00cd00\">ab</span>\
+             <span style=\"color:#cd0000\">cd</span>\
+             <span style=\"color:#cd0000;font-weight:bold\">e</span>\n</pre>\n"
+        );
+    }
+
+    #[test]
+    fn leaves_out_trailing_and_empty_cells() {
+        let mut canvas = Canvas::new(10, 3);
+        canvas.put_str(1, 0, "x", GREEN);
+        assert_eq!(
+            render(&canvas, &Theme::default()),
+            "<pre class=\"bonsai\">\n<span style=\"color:#00cd00\">x</span>\n\n</pre>\n"
+        );
+    }
+
+    #[test]
+    fn wraps_a_page_around_the_snippet() {
+        let config = Config {
+            html_page: true,
+            ..Config::default()
+        };
+        let canvas = Canvas::new(4, 1);
+        let page = document(&canvas, &config);
+        assert!(page.starts_with("<!DOCTYPE html>\n"));
+        assert!(page.contains("background: #000000; color: #e5e5e5;"));
+        assert!(page.contains("<pre class=\"bonsai\">\n</pre>\n</body>"));
+        assert_eq!(
+            document(&canvas, &Config::default()),
+            "<pre class=\"bonsai\">\n</pre>\n"
+        );
+    }
+}
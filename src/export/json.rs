@@ -0,0 +1,165 @@
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use serde_json::{json, Value};
+
+use crate::domain::{
+    canvas::Canvas,
+    layout::Layout,
+    topology::{Branch, Topology},
+};
+
+pub fn export(
+    canvas: &Canvas,
+    topology: &Topology,
+    layout: &Layout,
+    origin: (i32, i32),
+    path: &Path,
+) -> anyhow::Result<()> {
+    let text = document(canvas, topology, layout, origin)?;
+    fs::write(path, text).with_context(|| format!("couldnt write {}", path.display()))
+}
+
+// with the seed, life and multiplier the tree grew with, which --fit may have picked. origin
+// is where the tree area starts on the canvas, so branches and segments come out in canvas
+// cells like width and height
+pub fn document(
+    canvas: &Canvas,
+    topology: &Topology,
+    layout: &Layout,
+    origin: (i32, i32),
+) -> anyhow::Result<String> {
+    let document = json!({
+        "seed": layout.seed,
+        "life": layout.life_start,
+        "multiplier": layout.multiplier,
+        "width": canvas.width,
+        "height": canvas.height,
+        "branches": topology.len(),
+        "root": topology
+            .root()
+            .filter(|_| !topology.is_removed(0))
+            .map(|root| branch(topology, origin, 0, root)),
+    });
+    Ok(serde_json::to_string_pretty(&document)? + "\n")
+}
+
+// a branch with its children nested inside it
+fn branch(topology: &Topology, origin: (i32, i32), id: usize, branch: &Branch) -> Value {
+    let segments: Vec<Value> = branch
+        .segments
+        .iter()
+        .map(|segment| {
+            json!({
+                "y": origin.0 + segment.y,
+                "x": origin.1 + segment.x,
+                "text": segment.text,
+                "color": segment.attr.color,
+                "bold": segment.attr.bold,
+            })
+        })
+        .collect();
+    let children: Vec<Value> = branch
+        .children
+        .iter()
+        .filter(|&&child| !topology.is_removed(child))
+        .map(|&child| self::branch(topology, origin, child, &topology.branches[child]))
+        .collect();
+
+    json!({
+        "id": id,
+        "type": format!("{:?}", branch.branch_type),
+        "y": origin.0 + branch.y,
+        "x": origin.1 + branch.x,
+        "life": branch.life,
+        "parent": branch.parent,
+        "segments": segments,
+        "children": children,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::domain::{branch_type::BranchType, canvas::Attr, topology::Segment};
+
+    fn topology() -> Topology {
+        let mut topology = Topology::default();
+        let trunk = topology.add(None, BranchType::Trunk, 9, 5, 10);
+        let leaf = topology.add(Some(trunk), BranchType::Dead, 8, 6, 2);
+        topology.add(Some(trunk), BranchType::Dead, 8, 4, 2);
+        for (id, y, x, text) in [(trunk, 9, 5, "/|\\"), (leaf, 8, 6, "&&")] {
+            let segment = Segment {
+                y,
+                x,
+                text: text.to_owned(),
+                attr: Attr::new(2, true),
+            };
+            topology.push_segment(id, segment);
+        }
+        topology
+    }
+
+    fn parse(topology: &Topology, origin: (i32, i32)) -> Value {
+        let layout = Layout {
+            seed: 42,
+            life_start: 30,
+            multiplier: 7,
+            ..Layout::default()
+        };
+        let text = document(&Canvas::new(40, 20), topology, &layout, origin).unwrap();
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn records_what_regrows_the_tree() {
+        let document = parse(&topology(), (0, 0));
+        assert_eq!(document["seed"], 42);
+        assert_eq!(document["life"], 30);
+        assert_eq!(document["multiplier"], 7);
+        assert_eq!(document["width"], 40);
+        assert_eq!(document["height"], 20);
+        assert_eq!(document["branches"], 3);
+    }
+
+    #[test]
+    fn places_branches_on_the_canvas() {
+        let document = parse(&topology(), (2, 10));
+        let root = &document["root"];
+        assert_eq!(
+            (&root["y"], &root["x"]),
+            (&Value::from(11), &Value::from(15))
+        );
+        let segment = &root["segments"][0];
+        assert_eq!(
+            (&segment["y"], &segment["x"]),
+            (&Value::from(11), &Value::from(15))
+        );
+        assert_eq!(segment["text"], "/|\\");
+        assert_eq!(segment["color"], 2);
+        assert_eq!(segment["bold"], true);
+        let leaf = &root["children"][0];
+        assert_eq!(leaf["type"], "Dead");
+        assert_eq!(leaf["parent"], 0);
+        assert_eq!(
+            (&leaf["y"], &leaf["x"]),
+            (&Value::from(10), &Value::from(16))
+        );
+    }
+
+    #[test]
+    fn leaves_out_cut_branches() {
+        let mut topology = topology();
+        topology.cut(1);
+        let document = parse(&topology, (0, 0));
+        assert_eq!(document["branches"], 2);
+        let children = document["root"]["children"].as_array().unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0]["id"], 2);
+
+        topology.cut(0);
+        assert_eq!(parse(&topology, (0, 0))["root"], Value::Null);
+    }
+}
@@ -0,0 +1,73 @@
+use std::path::Path;
+
+use anyhow::bail;
+
+use crate::domain::{
+    canvas::Canvas,
+    config::{Config, Format},
+    layout::Layout,
+    topology::Topology,
+};
+
+use self::gif::Animation;
+
+pub mod ansi;
+pub mod asciicast;
+pub mod gif;
+
+mod font;
+mod html;
+mod json;
+mod png;
+mod raster;
+
+// write the canvas to config.export_file, the format is picked by file extension. layout
+// holds the seed, life and multiplier the tree grew with, after any --fit rerolls, and
+// origin is where its tree area starts on the canvas
+pub fn export(
+    canvas: &Canvas,
+    animation: Option<&Animation>,
+    topology: &Topology,
+    config: &Config,
+    layout: &Layout,
+    origin: (i32, i32),
+) -> anyhow::Result<()> {
+    let path = Path::new(&config.export_file);
+    match extension(config) {
+        Some("png") => png::export(canvas, config, path),
+        Some("html" | "htm") => html::export(canvas, config, path),
+        Some("gif") => gif::export(canvas, animation, config, path),
+        Some("json") => json::export(canvas, topology, layout, origin, path),
+        _ => bail!("unsupported export format: {}", path.display()),
+    }
+}
+
+// the canvas as text for stdout in config.format. the empty rows above the tree are left
+// out of text formats
+pub fn render(
+    canvas: &Canvas,
+    topology: &Topology,
+    config: &Config,
+    layout: &Layout,
+    origin: (i32, i32),
+) -> anyhow::Result<String> {
+    Ok(match config.format {
+        Format::Ansi => ansi::render(canvas).trim_start_matches('\n').to_owned(),
+        Format::Plain => ansi::render_plain(canvas)
+            .trim_start_matches('\n')
+            .to_owned(),
+        Format::Html => html::document(canvas, config),
+        Format::Json => json::document(canvas, topology, layout, origin)?,
+    })
+}
+
+// whether the export format needs every step of growth, not just the finished tree
+pub fn is_animated(config: &Config) -> bool {
+    extension(config) == Some("gif")
+}
+
+fn extension(config: &Config) -> Option<&str> {
+    Path::new(&config.export_file)
+        .extension()
+        .and_then(|ext| ext.to_str())
+}
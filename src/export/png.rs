@@ -0,0 +1,22 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use anyhow::Context;
+
+use crate::domain::{canvas::Canvas, config::Config};
+
+use super::raster::Raster;
+
+pub fn export(canvas: &Canvas, config: &Config, path: &Path) -> anyhow::Result<()> {
+    let raster = Raster::new(canvas, config.cell_width, config.cell_height)?;
+
+    let file = File::create(path).with_context(|| format!("couldnt create {}", path.display()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), raster.width, raster.height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(Raster::palette(&config.theme));
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&raster.pixels)?;
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use anyhow::bail;
+
+use crate::domain::{canvas::Canvas, theme::Theme};
+
+use super::font::{glyph, GLYPH_HEIGHT, GLYPH_WIDTH};
+
+// glyphs are designed on a 6x9 grid: a column of spacing on the right,
+// and a row of padding above and below
+const DESIGN_WIDTH: u32 = GLYPH_WIDTH as u32 + 1;
+const DESIGN_HEIGHT: u32 = GLYPH_HEIGHT as u32 + 2;
+
+// palette index of the background, indices below it are the 16 color pairs
+pub const BACKGROUND: u8 = 16;
+
+// an image of the canvas, one palette index per pixel
+pub struct Raster {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Raster {
+    // fails when the image would be too large to address
+    pub fn new(canvas: &Canvas, cell_width: u32, cell_height: u32) -> anyhow::Result<Self> {
+        let size = (canvas.width as u32)
+            .checked_mul(cell_width)
+            .zip((canvas.height as u32).checked_mul(cell_height))
+            .and_then(|(width, height)| {
+                let pixels = usize::try_from(width.checked_mul(height)?).ok()?;
+                Some((width, height, pixels))
+            });
+        let Some((width, height, pixels)) = size else {
+            bail!("image is too large, try a smaller --size or --cell-size");
+        };
+        let mut raster = Self {
+            width,
+            height,
+            pixels: vec![BACKGROUND; pixels],
+        };
+
+        for y in 0..canvas.height {
+            for (x, cell) in canvas.row(y).iter().enumerate() {
+                if let Some(cell) = cell {
+                    let color = cell.attr.color.rem_euclid(16) as u8;
+                    let origin = (x as u32 * cell_width, y as u32 * cell_height);
+                    raster.draw_glyph(
+                        origin,
+                        cell_width,
+                        cell_height,
+                        cell.ch,
+                        color,
+                        cell.attr.bold,
+                    );
+                }
+            }
+        }
+        Ok(raster)
+    }
+
+    // rgb triples for every palette index
+    pub fn palette(theme: &Theme) -> Vec<u8> {
+        theme
+            .palette
+            .iter()
+            .chain(Some(&theme.background))
+            .flat_map(|rgb| [rgb.0, rgb.1, rgb.2])
+            .collect()
+    }
+
+    // scale a glyph to the cell with nearest neighbour sampling
+    fn draw_glyph(
+        &mut self,
+        origin: (u32, u32),
+        cell_width: u32,
+        cell_height: u32,
+        ch: char,
+        color: u8,
+        bold: bool,
+    ) {
+        let rows = glyph(ch);
+        let lit = |gx: i64, gy: i64| {
+            (0..GLYPH_WIDTH as i64).contains(&gx)
+                && (0..GLYPH_HEIGHT as i64).contains(&gy)
+                && rows[gy as usize] & (1 << (GLYPH_WIDTH as i64 - 1 - gx)) != 0
+        };
+
+        for py in 0..cell_height {
+            let gy = i64::from(py * DESIGN_HEIGHT / cell_height) - 1;
+            for px in 0..cell_width {
+                let gx = i64::from(px * DESIGN_WIDTH / cell_width);
+                // bold smears every pixel one column to the right
+                if lit(gx, gy) || (bold && lit(gx - 1, gy)) {
+                    let index = (origin.1 + py) * self.width + origin.0 + px;
+                    self.pixels[index as usize] = color;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{canvas::Attr, theme::Rgb};
+
+    // (x, y) of every pixel not left as background
+    fn lit(raster: &Raster) -> Vec<(u32, u32)> {
+        (0..raster.pixels.len() as u32)
+            .filter(|&i| raster.pixels[i as usize] != BACKGROUND)
+            .map(|i| (i % raster.width, i / raster.width))
+            .collect()
+    }
+
+    #[test]
+    fn sizes_the_image_by_cells() {
+        let raster = Raster::new(&Canvas::new(3, 2), 4, 6).unwrap();
+        assert_eq!((raster.width, raster.height), (12, 12));
+        assert_eq!(raster.pixels.len(), 144);
+        assert!(lit(&raster).is_empty());
+    }
+
+    #[test]
+    fn refuses_images_too_large_to_address() {
+        assert!(Raster::new(&Canvas::new(10, 10), u32::MAX, 18).is_err());
+        assert!(Raster::new(&Canvas::new(10, 10), 12, u32::MAX / 4).is_err());
+    }
+
+    #[test]
+    fn draws_a_glyph_in_its_cell_and_color() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.put_str(1, 1, "#", Attr::new(18, false));
+        let raster = Raster::new(&canvas, 12, 18).unwrap();
+        let pixels = lit(&raster);
+        assert!(!pixels.is_empty());
+        assert!(pixels
+            .iter()
+            .all(|&(x, y)| (12..24).contains(&x) && (18..36).contains(&y)));
+        // pairs past 16 wrap around like the curses palette
+        assert!(pixels
+            .iter()
+            .all(|&(x, y)| raster.pixels[(y * raster.width + x) as usize] == 2));
+    }
+
+    #[test]
+    fn bold_is_wider() {
+        let draw = |bold| {
+            let mut canvas = Canvas::new(1, 1);
+            canvas.put_str(0, 0, "|", Attr::new(7, bold));
+            lit(&Raster::new(&canvas, 12, 18).unwrap()).len()
+        };
+        assert!(draw(true) > draw(false));
+    }
+
+    #[test]
+    fn palette_ends_with_the_background() {
+        let theme = Theme {
+            background: Rgb(1, 2, 3),
+            ..Theme::default()
+        };
+        let palette = Raster::palette(&theme);
+        assert_eq!(palette.len(), 17 * 3);
+        assert_eq!(&palette[48..], &[1, 2, 3]);
+        assert_eq!(&palette[3..6], &[205, 0, 0]);
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    io::{self, BufReader},
+    time::Duration,
+};
+
+use crate::curses::{doupdate, update_panels};
+
+use rustbonsai::domain::{feed::Feed, garden::Wilt};
+
+use crate::Tree;
+
+// how long to wait for more input while growth has caught up with the progress
+const FEED_POLL: Duration = Duration::from_millis(50);
+
+pub struct FeedGrowth {
+    feed: Feed,
+    // steps the current tree takes to grow
+    steps: u32,
+    // the user's message, shown above the progress
+    label: String,
+    dead: bool,
+}
+
+impl Tree {
+    // start reading stdin, once, and measure the tree so it grows in proportion to progress
+    pub(crate) fn start_feed(&mut self) {
+        let (feed, label) = match self.feed.take() {
+            Some(growth) => (growth.feed, growth.label),
+            None => (
+                Feed::spawn(BufReader::new(io::stdin())),
+                self.config.message.clone(),
+            ),
+        };
+        self.config.wilt = Wilt::Healthy;
+        self.feed = Some(FeedGrowth {
+            feed,
+            steps: 0,
+            label,
+            dead: false,
+        });
+        // measured with the progress in place, for the layout it is drawn in
+        self.config.message = self.feed_message();
+        let steps = self.count_steps();
+        if let Some(growth) = &mut self.feed {
+            growth.steps = steps;
+        }
+    }
+
+    // hold growth until the piped progress catches up with it
+    pub(crate) fn follow_feed(&mut self) {
+        loop {
+            let Some(growth) = &mut self.feed else {
+                return;
+            };
+            let allowed = (growth.feed.fraction * f64::from(growth.steps)).ceil() as u32;
+            let behind = self.grower.steps() < allowed || growth.feed.ended;
+            let timeout = if behind { Duration::ZERO } else { FEED_POLL };
+            if growth.feed.update(timeout) {
+                self.show_feed();
+            }
+            if behind || self.interrupt.is_some() {
+                return;
+            }
+            self.poll_keys();
+        }
+    }
+
+    fn show_feed(&mut self) {
+        let Some(growth) = &mut self.feed else {
+            return;
+        };
+        let dies = growth.feed.failed && !growth.dead;
+        growth.dead |= dies;
+        if dies {
+            self.wither();
+        }
+        self.config.message = self.feed_message();
+        self.draw_message();
+        update_panels();
+        doupdate();
+    }
+
+    fn feed_message(&self) -> String {
+        let Some(growth) = &self.feed else {
+            return String::new();
+        };
+        let status = if growth.feed.failed {
+            "failed".to_owned()
+        } else if growth.feed.ended {
+            "done".to_owned()
+        } else {
+            format!("{:.0}%", growth.feed.fraction * 100.0)
+        };
+        format!("{} {status}", growth.label).trim_start().to_owned()
+    }
+}
@@ -0,0 +1,215 @@
+use std::time::{Duration, Instant};
+
+use crate::curses::{doupdate, update_panels};
+
+use rustbonsai::{
+    domain::{
+        branch_type::BranchType,
+        canvas::{Attr, Canvas},
+        config::{BaseType, Config},
+        garden::{self, Wilt},
+        history::{self, Session},
+        keymap::Action,
+    },
+    export::ansi,
+};
+
+use crate::Tree;
+
+// size of one tree of the history forest, the bottom two rows hold its label
+const FOREST_TREE_WIDTH: i32 = 22;
+const FOREST_TREE_HEIGHT: i32 = 12;
+
+pub struct FocusTimer {
+    pub deadline: Instant,
+    length: Duration,
+    // steps the current tree takes to grow
+    steps: u32,
+    // the user's message, shown above the countdown
+    label: String,
+    running: bool,
+    // seconds left the last time the countdown was drawn
+    shown: Option<u64>,
+}
+
+impl Tree {
+    // grow the tree off screen first to learn how many steps it takes, so its growth can be
+    // spread over the whole session
+    pub(crate) fn start_focus(&mut self) {
+        let Some(length) = self.config.focus else {
+            return;
+        };
+        let label = match &self.focus {
+            Some(focus) => focus.label.clone(),
+            None => self.config.message.clone(),
+        };
+        self.config.wilt = Wilt::Healthy;
+        self.focus = Some(FocusTimer {
+            deadline: Instant::now() + length,
+            length,
+            steps: 0,
+            label,
+            running: true,
+            shown: None,
+        });
+        // measured with the countdown in place, for the layout it is drawn in
+        self.config.message = self.countdown(length);
+        let steps = self.count_steps();
+        if let Some(focus) = &mut self.focus {
+            focus.steps = steps;
+        }
+    }
+
+    // milliseconds to wait after this step of growth. during a focus session this keeps the
+    // countdown current and paces growth to end with the timer
+    pub(crate) fn focus_step(&mut self) -> u64 {
+        let Some(focus) = self.focus.as_mut().filter(|focus| focus.running) else {
+            return self.config.time_step;
+        };
+        let left = focus.deadline.saturating_duration_since(Instant::now());
+        let steps_left = focus.steps.saturating_sub(self.grower.steps()).max(1);
+        let secs = left.as_secs_f64().ceil() as u64;
+        if focus.shown != Some(secs) {
+            focus.shown = Some(secs);
+            self.config.message = self.countdown(left);
+            self.draw_message();
+        }
+        (left / steps_left).as_millis() as u64
+    }
+
+    // log the session once growth ends; quitting early leaves a withered tree on screen
+    pub(crate) fn end_focus(&mut self) {
+        let Some(focus) = self.focus.as_mut().filter(|focus| focus.running) else {
+            return;
+        };
+        focus.running = false;
+        let left = focus.deadline.saturating_duration_since(Instant::now());
+        let focused = focus.length.saturating_sub(left);
+        let label = focus.label.clone();
+
+        let completed = self.interrupt.is_none();
+        let status = if completed {
+            "done"
+        } else {
+            // stay on the withered tree, a new or regrown tree starts the next session
+            if self.interrupt == Some(Action::Quit) {
+                self.interrupt = None;
+            }
+            self.wither();
+            "withered"
+        };
+        self.config.message = format!("{label} {status}").trim_start().to_owned();
+        self.draw_message();
+        update_panels();
+        doupdate();
+
+        let session = Session {
+            end: garden::now(),
+            seed: self.config.seed,
+            minutes: (focused.as_secs() + 30) / 60,
+            completed,
+        };
+        if let Err(why) = history::log(&self.config.history_file, &session) {
+            self.notice = why.to_string();
+            self.draw_status();
+        }
+    }
+
+    fn countdown(&self, left: Duration) -> String {
+        let label = self.focus.as_ref().map_or("", |focus| &focus.label);
+        let secs = left.as_secs_f64().ceil() as u64;
+        let time = if secs >= 60 * 60 {
+            format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+        } else {
+            format!("{:02}:{:02}", secs / 60, secs % 60)
+        };
+        format!("{label} {time}").trim_start().to_owned()
+    }
+
+    // turn the leaves of the grown tree the color of a wilted garden tree
+    pub(crate) fn wither(&mut self) {
+        self.config.wilt = Wilt::Wilted;
+        self.grower.wilt = Wilt::Wilted;
+        let (dying, dead) = garden::WILTED_LEAVES;
+        for branch in &mut self.grower.topology.branches {
+            let color = match branch.branch_type {
+                BranchType::Dying => dying,
+                BranchType::Dead => dead,
+                _ => continue,
+            };
+            for segment in &mut branch.segments {
+                segment.attr.color = color;
+            }
+        }
+        self.redraw_tree(None, None);
+    }
+
+    // print past focus sessions as a forest of small trees, oldest first
+    pub(crate) fn show_history(&self) {
+        let sessions = match history::load(&self.config.history_file) {
+            Ok(sessions) => sessions,
+            Err(why) => {
+                eprintln!("{why}");
+                std::process::exit(1);
+            }
+        };
+        if sessions.is_empty() {
+            println!("no focus sessions yet, start one with `rustbonsai --focus 25m`");
+            return;
+        }
+
+        let now = garden::now();
+        let per_row = (self.config.canvas_width / FOREST_TREE_WIDTH).max(1) as usize;
+        for row in sessions.chunks(per_row) {
+            let mut forest = Canvas::new(row.len() as i32 * FOREST_TREE_WIDTH, FOREST_TREE_HEIGHT);
+            for (x, session) in (0..).step_by(FOREST_TREE_WIDTH as usize).zip(row) {
+                forest.paste(0, x, &self.grow_forest_tree(session));
+
+                let status = if session.completed { "" } else { " withered" };
+                let label = format!("{}m{status}", session.minutes);
+                let when = garden::days_ago(session.end, now);
+                for (y, text) in [
+                    (FOREST_TREE_HEIGHT - 2, label),
+                    (FOREST_TREE_HEIGHT - 1, when),
+                ] {
+                    let offset = (FOREST_TREE_WIDTH - text.chars().count() as i32) / 2;
+                    forest.put_str(y, x + offset.max(0), &text, Attr::new(8, false));
+                }
+            }
+            print!("{}", ansi::render(&forest));
+        }
+
+        let completed = sessions.iter().filter(|session| session.completed).count();
+        let minutes: u64 = sessions.iter().map(|session| session.minutes).sum();
+        println!(
+            "{} sessions, {completed} completed, {}h{:02}m of focus",
+            sessions.len(),
+            minutes / 60,
+            minutes % 60
+        );
+    }
+
+    // a small tree grown from a session's seed, longer sessions grow bigger trees
+    fn grow_forest_tree(&self, session: &Session) -> Canvas {
+        let mut config = Config {
+            headless: true,
+            seed: session.seed,
+            life_start: (8 + session.minutes as i32).min(40),
+            multiplier: 5,
+            // sizes above are picked for the small forest canvas already
+            shrink_to_fit: false,
+            base_type: BaseType::Small,
+            canvas_width: FOREST_TREE_WIDTH,
+            canvas_height: FOREST_TREE_HEIGHT - 2,
+            theme: self.config.theme.clone(),
+            ..Config::default()
+        };
+        if !session.completed {
+            config.wilt = Wilt::Wilted;
+        }
+        let mut tree = Self::new(config);
+        tree.init();
+        tree.grow_tree();
+        tree.canvas
+    }
+}
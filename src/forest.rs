@@ -0,0 +1,159 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    domain::{
+        config::{Align, BaseType, Config},
+        layout::{Layout, MIN_COLS},
+    },
+    grower::Grower,
+};
+
+// a kind of tree a forest mixes in, as its leaves and how bushy it grows
+pub struct Species {
+    // empty keeps the leaves configured
+    pub leaves: &'static str,
+    // relative to the default multiplier of 5
+    pub multiplier: i32,
+}
+
+pub const SPECIES: [Species; 4] = [
+    Species {
+        leaves: "",
+        multiplier: 5,
+    },
+    Species {
+        leaves: "^",
+        multiplier: 3,
+    },
+    Species {
+        leaves: "*",
+        multiplier: 7,
+    },
+    Species {
+        leaves: "%",
+        multiplier: 9,
+    },
+];
+
+// one tree of a forest and where it grows
+pub struct Planting {
+    pub layout: Layout,
+    pub grower: Grower,
+}
+
+// config.forest trees side by side across cols, each in a slot of its own so they never grow
+// into each other. seeds, species, slot widths and sizes all come from config.seed, the same
+// seed plants the same forest. none when the screen is too small for a tree
+pub fn plant(config: &Config, rows: i32, cols: i32) -> Option<Vec<Planting>> {
+    let count = (config.forest as i32).clamp(1, (cols / MIN_COLS).max(1));
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    // a shared ground line takes the bottom row
+    let (rows, base_type) = if config.ground {
+        (rows - 1, BaseType::None)
+    } else {
+        (rows, config.base_type)
+    };
+
+    // every slot is wide enough for a tree, what is left over is shared out unevenly
+    let weights: Vec<f64> = (0..count).map(|_| rng.gen_range(0.6..1.4)).collect();
+    let total: f64 = weights.iter().sum();
+    let spare = cols - count * MIN_COLS;
+    let mut x = 0;
+    let mut plantings = Vec::new();
+    for (i, weight) in weights.iter().enumerate() {
+        let slot_cols = if i as i32 == count - 1 {
+            cols - x
+        } else {
+            MIN_COLS + (f64::from(spare) * weight / total) as i32
+        };
+
+        let species = &SPECIES[rng.gen_range(0..SPECIES.len())];
+        // smaller trees look further away
+        let depth: f64 = rng.gen_range(0.6..=1.0);
+        let mut tree = Config {
+            seed: rng.gen(),
+            life_start: ((f64::from(config.life_start) * depth).round() as i32).max(1),
+            multiplier: (species.multiplier * config.multiplier / 5).clamp(1, 20),
+            base_type,
+            align: Align::Center,
+            offset: (0, 0),
+            message: String::new(),
+            ..config.clone()
+        };
+        if !species.leaves.is_empty() {
+            tree.leaves = species.leaves.to_owned();
+        }
+
+        let mut layout = Layout::new(&tree, rows, slot_cols)?;
+        layout.tree_x += x;
+        layout.base_x += x;
+        let grower = Grower::fitted(&tree, &layout);
+        plantings.push(Planting { layout, grower });
+        x += slot_cols;
+    }
+    Some(plantings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forest(count: usize, seed: u64) -> Config {
+        Config {
+            forest: count,
+            seed,
+            ..Config::default()
+        }
+    }
+
+    // where each tree grows, and from which seed
+    fn slots(plantings: &[Planting]) -> Vec<(i32, i32, u64)> {
+        plantings
+            .iter()
+            .map(|p| (p.layout.tree_x, p.layout.tree_cols, p.layout.seed))
+            .collect()
+    }
+
+    #[test]
+    fn plants_the_same_forest_from_a_seed() {
+        let first = plant(&forest(4, 7), 24, 120).unwrap();
+        let again = plant(&forest(4, 7), 24, 120).unwrap();
+        assert_eq!(slots(&first), slots(&again));
+        let other = plant(&forest(4, 8), 24, 120).unwrap();
+        assert_ne!(slots(&first), slots(&other));
+    }
+
+    #[test]
+    fn keeps_each_tree_in_its_own_slot() {
+        let plantings = plant(&forest(5, 7), 24, 120).unwrap();
+        assert_eq!(plantings.len(), 5);
+        let mut edge = 0;
+        for planting in &plantings {
+            let layout = planting.layout;
+            assert!(layout.tree_x >= edge);
+            assert!(layout.tree_cols >= MIN_COLS);
+            edge = layout.tree_x + layout.tree_cols;
+            assert!(layout.base_x >= layout.tree_x);
+        }
+        assert!(edge <= 120);
+    }
+
+    #[test]
+    fn plants_only_as_many_trees_as_fit() {
+        assert_eq!(plant(&forest(10, 7), 24, 40).unwrap().len(), 2);
+        assert_eq!(plant(&forest(3, 7), 24, 8).map(|p| p.len()), None);
+    }
+
+    #[test]
+    fn plants_in_the_ground_without_pots() {
+        let config = Config {
+            ground: true,
+            ..forest(3, 7)
+        };
+        for planting in plant(&config, 24, 120).unwrap() {
+            assert_eq!(planting.layout.base_type, BaseType::None);
+            // the bottom row is the ground line
+            assert_eq!(planting.layout.rows, 23);
+        }
+    }
+}
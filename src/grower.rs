@@ -0,0 +1,544 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    domain::{
+        branch_type::BranchType,
+        canvas::{Attr, Cell},
+        config::Config,
+        garden::Wilt,
+        layout::Layout,
+        theme::TreeColors,
+        topology::{Segment, Topology},
+    },
+    set_deltas::set_deltas,
+};
+
+// grows a tree one step at a time, each step drawing one piece of a branch. the host decides
+// when to step and how to show the cells that changed; nothing in here sleeps or touches
+// the terminal
+pub struct Grower {
+    pub topology: Topology,
+    // color of leaves grown from now on
+    pub wilt: Wilt,
+    colors: TreeColors,
+    rng: StdRng,
+    rows: i32,
+    cols: i32,
+    life_start: i32,
+    multiplier: i32,
+    leaves: String,
+    leaves_size: i32,
+    branch_limit: i32,
+    branches: i32,
+    shoots: i32,
+    shoot_counter: i32,
+    steps: u32,
+    // (top, left, bottom, right) of everything drawn so far, inside the area or not
+    bounds: Option<(i32, i32, i32, i32)>,
+    // branches still growing, the innermost last
+    stack: Vec<Frame>,
+}
+
+// a branch part way through growing
+struct Frame {
+    id: usize,
+    y: i32,
+    x: i32,
+    branch_type: BranchType,
+    life: i32,
+    shoot_cooldown: i32,
+    // movement rolled before an offshoot started growing, taken once the offshoot is done
+    pending: Option<(i32, i32)>,
+}
+
+// a tree with nothing left to grow
+impl Default for Grower {
+    fn default() -> Self {
+        Self {
+            topology: Topology::default(),
+            wilt: Wilt::Healthy,
+            colors: TreeColors::default(),
+            rng: StdRng::seed_from_u64(0),
+            rows: 0,
+            cols: 0,
+            life_start: 0,
+            multiplier: 0,
+            leaves: String::new(),
+            leaves_size: 0,
+            branch_limit: 0,
+            branches: 0,
+            shoots: 0,
+            shoot_counter: 0,
+            steps: 0,
+            bounds: None,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl Grower {
+    // a tree grown from config.seed, rooted at the bottom middle of an area of rows x cols
+    pub fn new(config: &Config, rows: i32, cols: i32) -> Self {
+        Self::seeded(
+            config,
+            config.seed,
+            rows,
+            cols,
+            config.life_start,
+            config.multiplier,
+        )
+    }
+
+    // a tree grown in the tree area of a layout, with the seed, life and multiplier it picked
+    pub fn fitted(config: &Config, layout: &Layout) -> Self {
+        Self::seeded(
+            config,
+            layout.seed,
+            layout.tree_rows,
+            layout.tree_cols,
+            layout.life_start,
+            layout.multiplier,
+        )
+    }
+
+    // the rest of the config with another seed, life and multiplier
+    pub fn seeded(
+        config: &Config,
+        seed: u64,
+        rows: i32,
+        cols: i32,
+        life_start: i32,
+        multiplier: i32,
+    ) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let shoot_counter = rand(&mut rng);
+        let mut grower = Self {
+            topology: Topology::default(),
+            wilt: config.wilt,
+            colors: config.theme.tree,
+            rng,
+            rows,
+            cols,
+            life_start,
+            multiplier,
+            leaves: config.leaves.clone(),
+            leaves_size: config.leaves_size,
+            branch_limit: config.branch_limit,
+            branches: 0,
+            shoots: 0,
+            shoot_counter,
+            steps: 0,
+            bounds: None,
+            stack: Vec::new(),
+        };
+        grower.start_branch(None, rows - 1, cols / 2, BranchType::Trunk, life_start);
+        grower
+    }
+
+    // grow the next piece of a branch and return the cells it drew inside the area, as
+    // (y, x, cell). returns nothing once the tree is done
+    pub fn step(&mut self) -> Vec<(i32, i32, Cell)> {
+        while let Some(top) = self.stack.len().checked_sub(1) {
+            if let Some((dx, dy)) = self.stack[top].pending.take() {
+                return self.grow_segment(dx, dy);
+            }
+            if self.stack[top].life <= 0 || self.past_branch_limit() {
+                self.stack.pop();
+                continue;
+            }
+
+            let frame = &mut self.stack[top];
+            frame.life -= 1;
+            let (branch_type, life, y) = (frame.branch_type, frame.life, frame.y);
+            let age = self.life_start - life;
+            let (dx, mut dy) = set_deltas(&mut self.rng, branch_type, life, age, self.multiplier);
+            if dy > 0 && y > (self.rows - 2) {
+                dy -= 1; // reduce dy if too close to the ground
+            }
+            self.stack[top].pending = Some((dx, dy));
+            self.branch_off(top);
+            self.stack[top].shoot_cooldown -= 1;
+        }
+        Vec::new()
+    }
+
+    // up to n steps, the cells changed by all of them
+    pub fn step_n(&mut self, n: usize) -> Vec<(i32, i32, Cell)> {
+        let mut cells = Vec::new();
+        for _ in 0..n {
+            if self.is_done() {
+                break;
+            }
+            cells.extend(self.step());
+        }
+        cells
+    }
+
+    // (rows, cols) of the area the tree grows in
+    pub fn size(&self) -> (i32, i32) {
+        (self.rows, self.cols)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn branches(&self) -> i32 {
+        self.branches
+    }
+
+    pub fn shoots(&self) -> i32 {
+        self.shoots
+    }
+
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    // (top, left, bottom, right) of the tree so far, including what fell outside the area
+    pub fn bounds(&self) -> Option<(i32, i32, i32, i32)> {
+        self.bounds
+    }
+
+    // whether any of the tree so far fell outside the area
+    pub fn spilled(&self) -> bool {
+        self.bounds.is_some_and(|(top, left, bottom, right)| {
+            top < 0 || left < 0 || bottom >= self.rows || right >= self.cols
+        })
+    }
+
+    fn start_branch(
+        &mut self,
+        parent: Option<usize>,
+        y: i32,
+        x: i32,
+        branch_type: BranchType,
+        life: i32,
+    ) {
+        self.branches += 1;
+        let id = self.topology.add(parent, branch_type, y, x, life);
+        self.stack.push(Frame {
+            id,
+            y,
+            x,
+            branch_type,
+            life,
+            shoot_cooldown: self.multiplier,
+            pending: None,
+        });
+    }
+
+    // maybe start an offshoot of the branch at index top of the stack, it grows before
+    // the branch moves on
+    fn branch_off(&mut self, top: usize) {
+        let Frame {
+            id,
+            y,
+            x,
+            branch_type,
+            life,
+            shoot_cooldown,
+            ..
+        } = self.stack[top];
+        let is_stem = matches!(
+            branch_type,
+            BranchType::Trunk | BranchType::ShootLeft | BranchType::ShootRight
+        );
+
+        // near-dead branch should branch into a lot of leaves
+        if life < 3 {
+            self.start_branch(Some(id), y, x, BranchType::Dead, life);
+        }
+        // dying trunk/branch should branch into a lot of leaves
+        else if is_stem && life < (self.multiplier + 2) {
+            self.start_branch(Some(id), y, x, BranchType::Dying, life);
+        }
+        // trunks should re-branch if not close to ground AND either randomly, or upon every <multiplier> steps
+        else if (branch_type == BranchType::Trunk && dice(&mut self.rng, 3) == 0)
+            || (life % self.multiplier == 0)
+        {
+            // if trunk is branching and not about to die, create another trunk with random life
+            if dice(&mut self.rng, 8) == 0 && life > 7 {
+                // reset shoot cooldown
+                self.stack[top].shoot_cooldown = self.multiplier * 2;
+                let trunk_life = life + (dice(&mut self.rng, 5) - 2);
+                self.start_branch(Some(id), y, x, BranchType::Trunk, trunk_life);
+            }
+            // otherwise create a shoot
+            else if shoot_cooldown <= 0 {
+                // reset shoot cooldown
+                self.stack[top].shoot_cooldown = self.multiplier * 2;
+
+                // first shoot is randomly directed
+                self.shoots += 1;
+                self.shoot_counter += 1;
+                let direction = if self.shoot_counter % 2 == 0 {
+                    BranchType::ShootLeft
+                } else {
+                    BranchType::ShootRight
+                };
+                self.start_branch(Some(id), y, x, direction, life + self.multiplier);
+            }
+        }
+    }
+
+    // move the branch on top of the stack and draw it there
+    fn grow_segment(&mut self, dx: i32, dy: i32) -> Vec<(i32, i32, Cell)> {
+        let Some(frame) = self.stack.last_mut() else {
+            return Vec::new();
+        };
+        frame.x += dx;
+        frame.y += dy;
+        let (id, y, x, branch_type, life) =
+            (frame.id, frame.y, frame.x, frame.branch_type, frame.life);
+
+        let attr = self.choose_color(branch_type);
+        let text = self.choose_string(branch_type, life, dx, dy);
+        self.steps += 1;
+        if let Some(last) = (text.chars().count() as i32).checked_sub(1) {
+            let (top, left, bottom, right) = self.bounds.unwrap_or((y, x, y, x));
+            self.bounds = Some((top.min(y), left.min(x), bottom.max(y), right.max(x + last)));
+        }
+
+        let cells = if (0..self.rows).contains(&y) {
+            (x..)
+                .zip(text.chars())
+                .filter(|(x, _)| (0..self.cols).contains(x))
+                .map(|(x, ch)| (y, x, Cell { ch, attr }))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.topology.push_segment(id, Segment { y, x, text, attr });
+        cells
+    }
+
+    fn past_branch_limit(&self) -> bool {
+        self.branch_limit > 0 && self.branches >= self.branch_limit
+    }
+
+    // based on type of tree, determine what color a branch should be. a thirsty tree yellows
+    // whatever the theme
+    fn choose_color(&mut self, branch_type: BranchType) -> Attr {
+        let (dying, dead) = self.wilt.leaf_colors().unwrap_or(self.colors.leaves);
+        match branch_type {
+            BranchType::Trunk | BranchType::ShootLeft | BranchType::ShootRight => {
+                if dice(&mut self.rng, 2) == 0 {
+                    self.colors.wood.0
+                } else {
+                    self.colors.wood.1
+                }
+            }
+            BranchType::Dying => Attr::new(dying, dice(&mut self.rng, 10) == 0),
+            BranchType::Dead => Attr::new(dead, dice(&mut self.rng, 3) == 0),
+        }
+    }
+
+    fn choose_string(
+        &mut self,
+        mut branch_type: BranchType,
+        life: i32,
+        dx: i32,
+        dy: i32,
+    ) -> String {
+        let fallback_char = "?";
+
+        if life < 4 {
+            branch_type = BranchType::Dying;
+        }
+
+        let text = match branch_type {
+            BranchType::Trunk => {
+                if dy == 0 {
+                    "/~"
+                } else if dx < 0 {
+                    "\\|"
+                } else if dx == 0 {
+                    "/|\\"
+                } else if dx > 0 {
+                    "|/"
+                } else {
+                    fallback_char
+                }
+            }
+            BranchType::ShootLeft => {
+                if dy > 0 {
+                    "\\"
+                } else if dy == 0 {
+                    "\\_"
+                } else if dx < 0 {
+                    "\\|"
+                } else if dx == 0 {
+                    "/|"
+                } else if dx > 0 {
+                    "/"
+                } else {
+                    fallback_char
+                }
+            }
+            BranchType::ShootRight => {
+                if dy > 0 {
+                    "/"
+                } else if dy == 0 {
+                    "_/"
+                } else if dx < 0 {
+                    "\\|"
+                } else if dx == 0 {
+                    "/|"
+                } else if dx > 0 {
+                    "/"
+                } else {
+                    fallback_char
+                }
+            }
+            BranchType::Dying | BranchType::Dead => {
+                if self.leaves_size > 0 {
+                    // counted in characters, leaves need not be ascii
+                    let end = dice(&mut self.rng, self.leaves_size) as usize;
+                    return self.leaves.chars().take(end + 1).collect();
+                } else {
+                    ""
+                }
+            }
+        };
+        text.to_owned()
+    }
+}
+
+pub(crate) fn dice(rng: &mut StdRng, sides: i32) -> i32 {
+    rand(rng) % sides
+}
+
+pub(crate) fn rand(rng: &mut StdRng) -> i32 {
+    rng.gen::<i32>().abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a default tree in the 80x20 area above the big base of an 80x24 screen
+    fn grower(seed: u64) -> Grower {
+        Grower::seeded(&Config::default(), seed, 20, 80, 64, 10)
+    }
+
+    // fnv-1a over "y,x,text;" of every segment, branch by branch
+    fn fingerprint(topology: &Topology) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for branch in &topology.branches {
+            for segment in &branch.segments {
+                let text = format!("{},{},{};", segment.y, segment.x, segment.text);
+                for byte in text.bytes() {
+                    hash = (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+                }
+            }
+        }
+        hash
+    }
+
+    fn segments(topology: &Topology) -> usize {
+        topology.branches.iter().map(|b| b.segments.len()).sum()
+    }
+
+    #[test]
+    fn grows_the_same_tree_as_recursive_growth() {
+        // taken from json exports of the recursive growth this replaced, -s 7 and -s 42
+        for (seed, branches, count, hash) in [
+            (7, 2064, 3391, 0x4f1f_9140_e86e_e895),
+            (42, 3920, 6407, 0x3dc2_0462_d23e_bc2d),
+        ] {
+            let mut grower = grower(seed);
+            while !grower.is_done() {
+                grower.step();
+            }
+            assert_eq!(grower.topology.branches.len(), branches);
+            assert_eq!(segments(&grower.topology), count);
+            assert_eq!(fingerprint(&grower.topology), hash);
+        }
+    }
+
+    #[test]
+    fn step_n_matches_single_steps() {
+        let mut single = grower(7);
+        let mut batched = grower(7);
+        while !batched.is_done() {
+            let mut cells = Vec::new();
+            for _ in 0..25 {
+                cells.extend(single.step());
+            }
+            let batch = batched.step_n(25);
+            assert_eq!(batch.len(), cells.len());
+            for ((y, x, cell), (by, bx, bcell)) in cells.iter().zip(&batch) {
+                assert_eq!((y, x, cell.ch, cell.attr), (by, bx, bcell.ch, bcell.attr));
+            }
+            assert_eq!(single.steps(), batched.steps());
+        }
+        assert!(single.is_done());
+        assert_eq!(
+            fingerprint(&single.topology),
+            fingerprint(&batched.topology)
+        );
+    }
+
+    #[test]
+    fn done_after_exhaustion() {
+        let mut grower = grower(7);
+        assert!(!grower.is_done());
+        while !grower.is_done() {
+            grower.step();
+        }
+        let (steps, branches) = (grower.steps(), grower.branches());
+        assert!(grower.step().is_empty());
+        assert!(grower.step_n(10).is_empty());
+        assert!(grower.is_done());
+        assert_eq!((grower.steps(), grower.branches()), (steps, branches));
+        assert!(Grower::default().is_done());
+    }
+
+    #[test]
+    fn spills_out_of_a_tiny_area() {
+        let mut grower = Grower::seeded(&Config::default(), 7, 4, 4, 64, 10);
+        assert_eq!(grower.bounds(), None);
+        assert!(!grower.spilled());
+        let mut cells = Vec::new();
+        while !grower.is_done() {
+            cells.extend(grower.step());
+        }
+        assert!(grower.spilled());
+        let (top, left, bottom, right) = grower.bounds().unwrap();
+        assert!(top < 0 && left < 0 && right >= 4);
+        assert!(bottom <= 3);
+        // only what fits in the area comes back to be drawn
+        assert!(!cells.is_empty());
+        assert!(cells
+            .iter()
+            .all(|&(y, x, _)| (0..4).contains(&y) && (0..4).contains(&x)));
+    }
+
+    #[test]
+    fn stops_at_the_branch_limit() {
+        for limit in [1, 8, 100] {
+            let config = Config {
+                branch_limit: limit,
+                ..Config::default()
+            };
+            let mut grower = Grower::seeded(&config, 7, 20, 80, 64, 10);
+            while !grower.is_done() {
+                grower.step();
+            }
+            assert_eq!(grower.branches(), limit);
+            assert_eq!(grower.topology.branches.len(), limit as usize);
+        }
+    }
+
+    #[test]
+    fn fits_a_roomy_area() {
+        let mut grower = Grower::seeded(&Config::default(), 7, 200, 400, 16, 3);
+        while !grower.is_done() {
+            grower.step();
+        }
+        assert!(!grower.spilled());
+        let (top, left, bottom, right) = grower.bounds().unwrap();
+        assert!(top >= 0 && left >= 0 && bottom < 200 && right < 400);
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    panic,
+    sync::atomic::{AtomicBool, AtomicI32, Ordering},
+};
+
+use once_cell::sync::OnceCell;
+
+use crate::curses::{curs_set, endwin, resetty, CURSOR_VISIBILITY};
+
+// set while curses owns the terminal
+static ARMED: AtomicBool = AtomicBool::new(false);
+// the first SIGINT or SIGTERM caught, 0 for none
+static CAUGHT: AtomicI32 = AtomicI32::new(0);
+// the terminal and its mode from before curses, for restoring it inside a signal handler
+static SHELL: OnceCell<(i32, libc::termios)> = OnceCell::new();
+
+// the terminal leaves sgr, cursor and alternate screen as it found them
+const RESET: &[u8] = b"\x1b[0m\x1b[?25h\x1b[?1049l";
+
+// restore the terminal before a panic message is printed, and turn SIGINT and SIGTERM into a
+// quit the main loop notices
+pub fn install() {
+    let report = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        report(info);
+    }));
+
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as usize;
+            libc::sigemptyset(&mut action.sa_mask);
+            // no SA_RESTART, so a blocking wgetch returns and the loop gets to see the signal
+            action.sa_flags = 0;
+            libc::sigaction(signal, &action, std::ptr::null_mut());
+        }
+    }
+}
+
+// curses is about to change the terminal mode
+pub fn arm() {
+    SHELL.get_or_init(|| {
+        let fd = unsafe { libc::open(c"/dev/tty".as_ptr(), libc::O_RDWR | libc::O_CLOEXEC) };
+        let fd = if fd < 0 { libc::STDOUT_FILENO } else { fd };
+        let mut mode = unsafe { std::mem::zeroed::<libc::termios>() };
+        unsafe { libc::tcgetattr(fd, &mut mode) };
+        (fd, mode)
+    });
+    ARMED.store(true, Ordering::SeqCst);
+}
+
+// curses has handed the terminal back
+pub fn disarm() {
+    ARMED.store(false, Ordering::SeqCst);
+}
+
+// the signal caught while curses had the terminal, if any
+pub fn caught() -> Option<i32> {
+    match CAUGHT.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
+
+fn restore() {
+    if ARMED.swap(false, Ordering::SeqCst) {
+        resetty();
+        curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+        endwin();
+    }
+}
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    // outside curses there is nothing to clean up, die the usual way
+    if !ARMED.load(Ordering::SeqCst) {
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+        return;
+    }
+    // a second signal means the loop did not get to quit, put the terminal back from here.
+    // only async-signal-safe calls: curses itself cant be used
+    if CAUGHT.swap(signal, Ordering::SeqCst) != 0 {
+        if let Some((fd, mode)) = SHELL.get() {
+            unsafe {
+                libc::tcsetattr(*fd, libc::TCSANOW, mode);
+                libc::write(*fd, RESET.as_ptr().cast(), RESET.len());
+            }
+        }
+        unsafe { libc::_exit(128 + signal) };
+    }
+}
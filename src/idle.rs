@@ -0,0 +1,77 @@
+use std::time::{Duration, Instant};
+
+use rustbonsai::domain::{
+    base::base_size,
+    falling::{FallingLeaves, TICK},
+    keymap::Action,
+    layout::Layout,
+};
+
+use crate::{update_screen, view::draw_canvas, Tree};
+
+impl Tree {
+    // wait for a key like wait_for_action, moving the sky and dropping leaves off the finished
+    // tree meanwhile if asked to. a tick at a time, so an idle screensaver stays cheap
+    pub(crate) fn idle(&mut self, timeout: Option<Duration>) -> Option<Action> {
+        let rate = self.config.falling_leaves.filter(|_| !self.config.headless);
+        if rate.is_none() && self.sky.is_none() {
+            return self.wait_for_action(timeout);
+        }
+
+        if let Some(rate) = rate.filter(|_| self.falling.is_none()) {
+            self.hide_view();
+            self.falling = Some(FallingLeaves::new(
+                self.grown(),
+                &self.leaves(),
+                self.floor(),
+                self.config.seed,
+                rate,
+            ));
+        }
+        let start = Instant::now();
+        loop {
+            let tick = match timeout {
+                Some(timeout) => match timeout.saturating_sub(start.elapsed()) {
+                    Duration::ZERO => return None,
+                    left => left.min(TICK),
+                },
+                None => TICK,
+            };
+            if let Some(action) = self.wait_for_action(Some(tick)) {
+                return Some(action);
+            }
+            if let Some(falling) = &mut self.falling {
+                falling.tick();
+                draw_canvas(self.objects.tree_win, &falling.frame());
+            }
+            self.draw_sky();
+            update_screen(0);
+        }
+    }
+
+    // the row falling leaves land on in each column, on top of a base or the ground. none
+    // where there is nothing to land on
+    fn floor(&self) -> Vec<Option<i32>> {
+        let mut floor = vec![None; self.canvas.width.max(0) as usize];
+        if self.forest.is_empty() {
+            mark_base(&mut floor, &self.layout);
+            return floor;
+        }
+        if self.config.ground {
+            floor.fill(Some(self.canvas.height - 2));
+        }
+        for planting in &self.forest {
+            mark_base(&mut floor, &planting.layout);
+        }
+        floor
+    }
+}
+
+fn mark_base(floor: &mut [Option<i32>], layout: &Layout) {
+    let (width, _) = base_size(&layout.base_type);
+    for x in layout.base_x..layout.base_x + width {
+        if let Some(row) = usize::try_from(x).ok().and_then(|x| floor.get_mut(x)) {
+            *row = Some(layout.base_y - 1);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    panic,
+    thread::{self, JoinHandle},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    domain::{
+        base::{ground, GROUND},
+        canvas::Canvas,
+        config::Config,
+        layout::MIN_COLS,
+    },
+    forest,
+};
+
+// smallest and biggest share of its slot a tree of the landscape fills
+const FILL: (f64, f64) = (0.2, 0.6);
+
+// an endless strip of trees standing on a ground line, for panning across. trees are grown
+// headless a screen ahead of the view, into a pad wider than the screen, and the stretch after
+// that grows on a background thread so fitting its trees never holds up the pan
+pub struct Landscape {
+    config: Config,
+    rng: StdRng,
+    rows: i32,
+    cols: i32,
+    // the view starting at column 0, and what has been grown to the right of it
+    pad: Canvas,
+    // the stretch that goes on the right end of the pad next
+    next: Option<JoinHandle<Canvas>>,
+}
+
+impl Landscape {
+    // the same seed grows the same landscape
+    pub fn new(config: &Config, rows: i32, cols: i32) -> Self {
+        let mut landscape = Self {
+            config: Config {
+                ground: true,
+                message: String::new(),
+                ..config.clone()
+            },
+            rng: StdRng::seed_from_u64(config.seed),
+            rows,
+            cols,
+            pad: Canvas::new(0, rows),
+            next: None,
+        };
+        while landscape.pad.width < landscape.cols * 2 {
+            landscape.extend();
+        }
+        landscape
+    }
+
+    // what is on screen, cols x rows
+    pub fn view(&self) -> Canvas {
+        let mut view = Canvas::new(self.cols, self.rows);
+        view.paste(0, 0, &self.pad);
+        view
+    }
+
+    // pan a column to the right, growing more trees once less than a screen is left ahead
+    pub fn advance(&mut self) {
+        let mut pad = Canvas::new(self.pad.width - 1, self.rows);
+        pad.paste(0, -1, &self.pad);
+        self.pad = pad;
+        if self.pad.width < self.cols * 2 {
+            self.extend();
+        }
+    }
+
+    // add the next stretch to the right end of the pad and start growing the one after it
+    fn extend(&mut self) {
+        let next = self.next.take().unwrap_or_else(|| self.grow_next());
+        // started a screen ago, so rarely still growing
+        let stretch = next.join().unwrap_or_else(|err| panic::resume_unwind(err));
+        self.next = Some(self.grow_next());
+
+        let mut pad = Canvas::new(self.pad.width + stretch.width, self.rows);
+        pad.paste(0, 0, &self.pad);
+        pad.paste(0, self.pad.width, &stretch);
+        self.pad = pad;
+    }
+
+    // start growing a screen wide stretch of a few trees. picked here so the same seed still
+    // grows the same landscape
+    fn grow_next(&mut self) -> JoinHandle<Canvas> {
+        let (rows, width) = (self.rows, self.cols.max(MIN_COLS));
+        let config = Config {
+            seed: self.rng.gen(),
+            forest: self.rng.gen_range(1..=3),
+            // fitted, so nothing is cut off at the edge of its slot
+            fit: Some(self.rng.gen_range(FILL.0..FILL.1)),
+            ..self.config.clone()
+        };
+        thread::spawn(move || grow(&config, rows, width))
+    }
+}
+
+fn grow(config: &Config, rows: i32, width: i32) -> Canvas {
+    let mut stretch = Canvas::new(width, rows);
+    for mut planting in forest::plant(config, rows, width).unwrap_or_default() {
+        while !planting.grower.is_done() {
+            for (y, x, cell) in planting.grower.step() {
+                stretch.set(y, planting.layout.tree_x + x, Some(cell));
+            }
+        }
+    }
+    stretch.put_str(rows - 1, 0, &ground(width), GROUND);
+    stretch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn landscape(seed: u64) -> Landscape {
+        let config = Config {
+            seed,
+            ..Config::default()
+        };
+        Landscape::new(&config, 12, 32)
+    }
+
+    fn text(canvas: &Canvas) -> Vec<String> {
+        (0..canvas.height)
+            .map(|y| {
+                let row = canvas.row(y).iter();
+                row.map(|cell| cell.map_or(' ', |cell| cell.ch)).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn grows_the_same_landscape_from_a_seed() {
+        let (mut first, mut again) = (landscape(7), landscape(7));
+        for _ in 0..40 {
+            first.advance();
+            again.advance();
+        }
+        assert_eq!(text(&first.view()), text(&again.view()));
+        assert_ne!(text(&first.view()), text(&landscape(8).view()));
+    }
+
+    #[test]
+    fn pans_a_column_at_a_time() {
+        let mut landscape = landscape(7);
+        let before = text(&landscape.view());
+        landscape.advance();
+        let after = text(&landscape.view());
+        for (before, after) in before.iter().zip(&after) {
+            assert_eq!(before[1..], after[..after.len() - 1]);
+        }
+    }
+
+    #[test]
+    fn stands_on_ground_a_screen_ahead() {
+        let mut landscape = landscape(7);
+        for _ in 0..100 {
+            landscape.advance();
+            assert!(landscape.pad.width >= landscape.cols * 2);
+        }
+        let view = landscape.view();
+        assert_eq!((view.width, view.height), (32, 12));
+        assert_eq!(text(&view)[11], ground(32));
+    }
+}
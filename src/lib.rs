@@ -0,0 +1,11 @@
+// growing and rendering bonsai trees without a terminal, shared by the rustbonsai binary
+// and usable from other programs
+pub mod domain;
+pub mod export;
+pub mod forest;
+pub mod grower;
+pub mod landscape;
+#[cfg(feature = "ratatui")]
+pub mod widget;
+
+mod set_deltas;
@@ -1,97 +1,290 @@
 #![allow(dead_code)]
 
-extern crate ncurses;
-use std::{sync::Mutex, thread::sleep, time::Duration};
-
-use domain::{branch_type::BranchType, config::BaseType};
-use ncurses::{
-    cbreak, clear, curs_set, del_panel, delwin, doupdate, endwin, getmaxy, getmaxyx, has_colors,
-    init_pair, mvwinch, mvwprintw, new_panel, newwin, nodelay, noecho, overlay, overwrite,
-    pair_content, refresh, savetty, start_color, stdscr, timeout, update_panels,
-    use_default_colors, wattroff, wattron, wgetch, wprintw, A_BOLD, COLORS, COLOR_BLACK,
-    COLOR_PAIR, ERR,
+use std::{
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use crate::curses::{
+    cbreak, clear, curs_set, del_panel, delwin, doupdate, endwin, getmaxyx, has_colors, hide_panel,
+    init_pair, keypad, mvwaddstr, mvwprintw, new_panel, newwin, nodelay, noecho, refresh, savetty,
+    start_color, stdscr, top_panel, update_panels, use_default_colors, wattrset, ToCurses, COLORS,
+    COLOR_BLACK, CURSOR_VISIBILITY, ERR, NULL, WINDOW,
 };
 use once_cell::sync::OnceCell;
 use rand::{rngs::StdRng, Rng, SeedableRng};
-use set_deltas::set_deltas;
-
-use crate::domain::{config::Config, counters::Counters, ncurses_objects::NcursesObjects};
-
-mod domain;
-mod set_deltas;
+use rustbonsai::{
+    domain::{
+        base::{base_art, base_size, ground, GROUND},
+        canvas::{Attr, Canvas, Cell},
+        config::{Config, Mode},
+        falling::FallingLeaves,
+        garden::{self, Garden, Wilt},
+        keymap::Action,
+        layout::{Layout, MIN_COLS, MIN_ROWS},
+        progress::{self, Saved},
+        sky::Sky,
+        viewport::Viewport,
+    },
+    export::{self, asciicast::Recorder, gif::Animation},
+    forest::{self, Planting},
+    grower::Grower,
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{feed::FeedGrowth, focus::FocusTimer, ncurses_objects::NcursesObjects};
+
+mod controls;
+mod curses;
+mod feed;
+mod focus;
+mod guard;
+mod idle;
+mod ncurses_objects;
+mod panorama;
+mod pruning;
+mod sky;
+mod transition;
+mod view;
 
 static RNG: OnceCell<Mutex<StdRng>> = OnceCell::new();
 
 fn main() {
+    guard::install();
     let mut tree = Tree::from_args();
+    match tree.config.mode {
+        Mode::Tree => {}
+        Mode::Garden => tree.open_garden(),
+        Mode::Water => {
+            water_garden(&tree.config.garden_file);
+            return;
+        }
+        Mode::History => {
+            tree.show_history();
+            return;
+        }
+    }
+    if tree.config.load {
+        tree.load_progress();
+    }
 
     RNG.set(Mutex::new(StdRng::seed_from_u64(tree.config.seed)))
         .unwrap();
 
-    loop {
+    if tree.config.headless {
         tree.init();
         tree.grow_tree();
-        if tree.config.load {
-            tree.config.target_branch_count = 0;
+        tree.write_outputs();
+        if tree.config.once {
+            tree.print_screen();
         }
-        if tree.config.infinite {
-            timeout(tree.config.time_wait * 1000);
-            if tree.check_key_press() {
-                break;
-            }
-        }
-        if !tree.config.infinite {
+        return;
+    }
+
+    loop {
+        if tree.config.panorama.is_some() {
+            tree.run_panorama();
             break;
         }
+        if tree.config.focus.is_some() {
+            tree.start_focus();
+        }
+        if tree.config.progress_from_stdin {
+            tree.start_feed();
+        }
+        tree.init();
+        tree.grow_tree();
+        tree.end_focus();
+        // only the first tree catches up to where it was saved or left
+        tree.config.target_branch_count = 0;
+        tree.config.cuts.clear();
+        tree.config.growth = None;
+
+        // a key pressed during growth wins, otherwise wait for one
+        let action = match tree.interrupt.take() {
+            Some(action) => Some(action),
+            None if tree.config.infinite => {
+                let time_wait = Duration::from_secs(tree.config.time_wait.max(0) as u64);
+                tree.wait_and_leave(time_wait)
+            }
+            None if tree.config.print_tree || tree.config.prune => break,
+            None => tree.idle(None),
+        };
+        match action {
+            Some(Action::Regrow) => {}
+            Some(Action::NewTree) => tree.config.seed = new_seed(),
+            // infinite mode moves on to a new tree after time_wait
+            None if tree.config.infinite => tree.config.seed = new_seed(),
+            _ => break,
+        }
     }
 
-    if tree.config.print_tree {
-        tree.finish();
-
-        // overlay all windows onto stdscr
-        overlay(tree.objects.base_win, stdscr());
-        overlay(tree.objects.tree_win, stdscr());
-        overwrite(tree.objects.message_border_win, stdscr());
-        overwrite(tree.objects.message_win, stdscr());
-
-        printstdscr();
-    } else {
-        wgetch(tree.objects.tree_win);
-        tree.finish();
-    }
-    //
-    // loop {
-    //     sleep(Duration::from_secs(1));
-    // }
-}
+    tree.exit_if_caught();
+    if tree.config.prune {
+        tree.prune();
+        tree.exit_if_caught();
+    }
 
-// print stdscr to terminal window
-fn printstdscr() {
-    todo!();
+    tree.finish();
+    if tree.config.print_tree {
+        tree.print_screen();
+    }
+    tree.write_outputs();
 }
 
-#[derive(Default)]
 struct Tree {
     config: Config,
     objects: NcursesObjects,
-    counters: Counters,
+    canvas: Canvas,
+    // message box drawn above the tree
+    overlay: Canvas,
+    recorder: Option<Recorder>,
+    animation: Option<Animation>,
+    // where the tree and base go on the screen
+    layout: Layout,
+    // the layout count_steps measured the next tree in
+    planned: Option<Layout>,
+    // the tree being grown, and its branches once done
+    grower: Grower,
+    // with --forest, the trees grown instead of the one above
+    forest: Vec<Planting>,
+    // with --scroll, the part of the whole tree on screen
+    viewport: Viewport,
+    // with --weather, the sky behind the tree and when it next moves
+    sky: Option<Sky>,
+    sky_due: Instant,
+    // with --falling-leaves, the grown tree dropping its leaves
+    falling: Option<FallingLeaves>,
+    // action that cut growth short, handled once the tree returns
+    interrupt: Option<Action>,
+    paused: bool,
+    notice: String,
+    focus: Option<FocusTimer>,
+    feed: Option<FeedGrowth>,
 }
 
 impl Tree {
     fn from_args() -> Self {
+        Self::new(Config::from_args())
+    }
+
+    fn new(config: Config) -> Self {
+        let objects = if config.headless {
+            NcursesObjects::headless()
+        } else if config.progress_from_stdin {
+            NcursesObjects::on_tty()
+        } else {
+            NcursesObjects::default()
+        };
         Self {
-            config: Config::from_args(),
-            objects: NcursesObjects::default(),
-            counters: Counters::default(),
+            config,
+            objects,
+            canvas: Canvas::default(),
+            overlay: Canvas::default(),
+            recorder: None,
+            animation: None,
+            layout: Layout::default(),
+            planned: None,
+            grower: Grower::default(),
+            forest: Vec::new(),
+            viewport: Viewport::default(),
+            sky: None,
+            sky_due: Instant::now(),
+            falling: None,
+            interrupt: None,
+            paused: false,
+            notice: String::new(),
+            focus: None,
+            feed: None,
         }
     }
 
     fn init(&mut self) {
+        // a screensaver left running sees the day go by
+        self.config.refresh_theme();
+        self.falling = None;
+        if !self.config.headless {
+            self.init_terminal();
+        }
+
+        // define and draw windows, then create panels
+        if !self.config.headless {
+            self.hide_view();
+        }
+        self.draw_wins();
+        self.draw_message();
+
+        if !self.config.record_file.is_empty() && self.recorder.is_none() {
+            self.recorder = Some(Recorder::new(
+                self.canvas.width,
+                self.canvas.height,
+                self.config.time_step,
+                !self.config.headless,
+            ));
+        }
+        if export::is_animated(&self.config) {
+            self.animation = Some(Animation::new(self.config.gif_batch));
+        }
+        let screen = self.screen();
+        if let Some(recorder) = &mut self.recorder {
+            recorder.frame(&screen);
+        }
+    }
+
+    // write the export and recording requested on the command line, exits on failure
+    fn write_outputs(&self) {
+        let mut result = Ok(());
+        if !self.config.export_file.is_empty() {
+            result = export::export(
+                &self.picture(),
+                self.animation.as_ref(),
+                &self.grower.topology,
+                &self.config,
+                &self.layout,
+                self.origin(),
+            );
+        }
+        if let (Ok(()), Some(recorder)) = (&result, &self.recorder) {
+            result = recorder.save(Path::new(&self.config.record_file));
+        }
+        if let Err(why) = result {
+            eprintln!("couldnt write output: {why}");
+            std::process::exit(1);
+        }
+    }
+
+    // write the finished tree to stdout in the format asked for, exits on failure
+    fn print_screen(&self) {
+        match export::render(
+            &self.picture(),
+            &self.grower.topology,
+            &self.config,
+            &self.layout,
+            self.origin(),
+        ) {
+            // a reader that went away early, like head, is not an error
+            Ok(text) => _ = io::stdout().write_all(text.as_bytes()),
+            Err(why) => {
+                eprintln!("couldnt render tree: {why}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    fn init_terminal(&self) {
+        guard::arm();
         savetty();
         noecho();
-        curs_set(ncurses::CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+        curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
         cbreak();
         nodelay(stdscr(), true);
+        // arrow keys pan the view
+        if self.config.scroll {
+            keypad(stdscr(), true);
+        }
 
         // if terminal has color capabilities, use them
         if has_colors() {
@@ -123,351 +316,484 @@ impl Tree {
         } else {
             println!("Warning: terminal does not have color support.");
         }
-
-        // define and draw windows, then create panels
-        self.draw_wins();
-        self.draw_message();
     }
 
     fn grow_tree(&mut self) {
-        let mut max_y = 0;
-        let mut max_x = 0;
-        getmaxyx(self.objects.tree_win, &mut max_y, &mut max_x);
-
-        // reset counters
-        self.counters.shoots = 0;
-        self.counters.branches = 0;
-        self.counters.shoot_counter = rand();
-
-        if self.config.verbose {
-            mvwprintw(
-                self.objects.tree_win,
-                2,
-                5,
-                format!("maxX: {max_x}, maxY: {max_y}").as_str(),
-            );
+        let (max_y, max_x) = self.tree_size();
+        self.print_verbose(2, &format!("maxX: {max_x}, maxY: {max_y}"));
+        if !self.forest.is_empty() {
+            self.grow_forest();
+            return;
         }
 
-        self.branch(
-            max_y - 1,
-            max_x / 2,
-            BranchType::Trunk,
-            self.config.life_start,
-        );
+        self.grower = Grower::fitted(&self.config, &self.layout);
+        while !self.grower.is_done() && self.interrupt.is_none() {
+            let cells = self.grower.step();
+            self.draw_cells(&cells, self.layout.tree_x);
+            self.print_verbose(4, &format!("shoots: {}", self.grower.shoots()));
+            self.print_verbose(5, &format!("branches: {}", self.grower.branches()));
+            self.flush_frame();
+        }
+
+        // a loaded tree comes back pruned the way it was saved
+        if !self.config.cuts.is_empty() {
+            self.grower.topology.set_cuts(&self.config.cuts);
+            self.redraw_tree(None, None);
+        }
+
+        if !self.config.headless {
+            self.draw_sky();
+            update_panels();
+            doupdate();
+        }
+    }
 
-        update_panels();
-        doupdate();
+    // grow the trees of a forest together, a step of each per frame
+    fn grow_forest(&mut self) {
+        while self
+            .forest
+            .iter()
+            .any(|planting| !planting.grower.is_done())
+            && self.interrupt.is_none()
+        {
+            for i in 0..self.forest.len() {
+                let cells = self.forest[i].grower.step();
+                self.draw_cells(&cells, self.forest[i].layout.tree_x);
+            }
+            self.flush_frame();
+        }
+
+        if !self.config.headless {
+            self.draw_sky();
+            update_panels();
+            doupdate();
+        }
     }
 
-    fn branch(&mut self, mut y: i32, mut x: i32, branch_type: BranchType, mut life: i32) {
-        self.counters.branches += 1;
-        let mut dx;
-        let mut dy;
-        let mut shoot_cooldown = self.config.multiplier;
+    // branches grown so far, over all the trees of a forest
+    fn branches(&self) -> i32 {
+        if self.forest.is_empty() {
+            self.grower.branches()
+        } else {
+            self.forest
+                .iter()
+                .map(|planting| planting.grower.branches())
+                .sum()
+        }
+    }
 
-        while life > 0 {
-            life -= 1;
-            let age = self.config.life_start - life;
+    // grow the tree off screen to learn how many steps it takes. the layout is kept for
+    // draw_wins, so any --fit trials run once
+    pub(crate) fn count_steps(&mut self) -> u32 {
+        let mut rows = 0;
+        let mut cols = 0;
+        getmaxyx(stdscr(), &mut rows, &mut cols);
+        let Some(layout) = Layout::new(&self.config, rows, cols) else {
+            return 0;
+        };
+        self.planned = Some(layout);
+        // layout already did any fitting, this grows the same tree
 
-            (dx, dy) = set_deltas(branch_type, life, age, self.config.multiplier);
+        let mut grower = Grower::fitted(&self.config, &layout);
+        while !grower.is_done() {
+            grower.step();
+        }
+        grower.steps()
+    }
 
-            let max_y = getmaxy(self.objects.tree_win);
-            if dy > 0 && y > (max_y - 2) {
-                dy -= 1; // reduce dy if too close to the ground
+    // show a step of growth in live mode, and record it
+    fn flush_frame(&mut self) {
+        if self.recorder.is_some() || self.animation.is_some() {
+            let screen = self.screen();
+            if let Some(recorder) = &mut self.recorder {
+                recorder.frame(&screen);
             }
-
-            // near-dead branch should branch into a lot of leaves
-            if life < 3 {
-                self.branch(y, x, BranchType::Dead, life);
+            if let Some(animation) = &mut self.animation {
+                animation.frame(&screen);
             }
-            // dying trunk/branch should branch into a lot of leaves
-            else if (BranchType::Trunk == branch_type
-                || BranchType::ShootLeft == branch_type
-                || BranchType::ShootRight == branch_type)
-                && life < (self.config.multiplier + 2)
-            {
-                self.branch(y, x, BranchType::Dying, life);
+        }
+        // a loaded or garden tree regrows instantly up to where it was saved
+        let catching_up = self.branches() < self.config.target_branch_count;
+        if self.config.live && !self.config.headless && !catching_up {
+            if !self.viewport.is_home() {
+                self.show_view();
             }
-            // trunks should re-branch if not close to ground AND either randomly, or upon every <multiplier> steps
-            else if (BranchType::Trunk == branch_type && (dice(3)) == 0)
-                || (life % self.config.multiplier == 0)
-            {
-                // if trunk is branching and not about to die, create another trunk with random life
-                if dice(8) == 0 && life > 7 {
-                    // reset shoot cooldown
-                    shoot_cooldown = self.config.multiplier * 2;
-                    self.branch(y, x, BranchType::Trunk, life + (dice(5) - 2));
-                }
-                // otherwise create a shoot
-                else if shoot_cooldown <= 0 {
-                    // reset shoot cooldown
-                    shoot_cooldown = self.config.multiplier * 2;
-
-                    let shoot_life = life + self.config.multiplier;
-
-                    // first shoot is randomly directed
-                    self.counters.shoots += 1;
-                    self.counters.shoot_counter += 1;
-                    if self.config.verbose {
-                        mvwprintw(
-                            self.objects.tree_win,
-                            4,
-                            5,
-                            format!("shoots: {}", self.counters.shoots).as_str(),
-                        );
-                    }
-                    // create shoot
-                    let direction = match self.counters.shoot_counter % 2 {
-                        0 => BranchType::ShootLeft,
-                        1 => BranchType::ShootRight,
-                        _ => BranchType::Dead,
-                    };
-                    self.branch(y, x, direction, shoot_life);
-                }
+            self.draw_sky();
+            update_screen(self.focus_step());
+            let start = Instant::now();
+            self.poll_keys();
+            // time spent paused does not count towards a focus session
+            if let Some(focus) = &mut self.focus {
+                focus.deadline += start.elapsed();
             }
-            shoot_cooldown -= 1;
+            self.follow_feed();
+        }
+    }
 
-            if self.config.verbose {
-                mvwprintw(self.objects.tree_win, 5, 5, format!("dx: {dx}").as_str());
-                mvwprintw(self.objects.tree_win, 6, 5, format!("dy: {dy}").as_str());
-                mvwprintw(
+    // draw cells of a tree area starting at column tree_x onto the tree window, mirrored onto
+    // the canvas
+    fn draw_cells(&mut self, cells: &[(i32, i32, Cell)], tree_x: i32) {
+        for &(y, x, cell) in cells {
+            let x = tree_x + x;
+            self.canvas.set(y, x, Some(cell));
+            if !self.config.headless {
+                wattrset(self.objects.tree_win, cell.attr.to_curses());
+                mvwaddstr(
                     self.objects.tree_win,
-                    7,
-                    5,
-                    format!("branchtype: {branch_type:?}").as_str(),
+                    y,
+                    x,
+                    cell.ch.encode_utf8(&mut [0; 4]),
                 );
-                mvwprintw(
-                    self.objects.tree_win,
-                    8,
-                    5,
-                    format!("shootCooldown: {shoot_cooldown:?}").as_str(),
-                );
-            }
-
-            // move in x and y directions
-            x += dx;
-            y += dy;
-
-            self.choose_color(branch_type);
-
-            // choose string to use for this branch
-            let branchstr = self.choose_string(branch_type, life, dx, dy);
-
-            mvwprintw(self.objects.tree_win, y, x, branchstr);
-            wattroff(self.objects.tree_win, A_BOLD());
-            if self.config.live {
-                update_screen(self.config.time_step);
             }
         }
     }
 
-    // based on type of tree, determine what color a branch should be
-    fn choose_color(&self, branch_type: BranchType) {
-        match branch_type {
-            BranchType::Trunk | BranchType::ShootLeft | BranchType::ShootRight => {
-                if dice(2) == 0 {
-                    wattron(self.objects.tree_win, A_BOLD() | COLOR_PAIR(11));
-                } else {
-                    wattron(self.objects.tree_win, COLOR_PAIR(3));
-                }
-            }
-            BranchType::Dying => {
-                if dice(10) == 0 {
-                    wattron(self.objects.tree_win, A_BOLD() | COLOR_PAIR(2));
-                } else {
-                    wattron(self.objects.tree_win, COLOR_PAIR(2));
-                }
-            }
-            BranchType::Dead => {
-                if dice(3) == 0 {
-                    wattron(self.objects.tree_win, A_BOLD() | COLOR_PAIR(10));
-                } else {
-                    wattron(self.objects.tree_win, COLOR_PAIR(10));
-                }
-            }
+    // debug output in the top left corner of the tree window
+    fn print_verbose(&self, y: i32, text: &str) {
+        if self.config.verbose && !self.config.headless {
+            mvwprintw(self.objects.tree_win, y, 5, text);
         }
     }
 
-    fn choose_string(&self, mut branch_type: BranchType, life: i32, dx: i32, dy: i32) -> &str {
-        let fallback_char = "?";
+    // (rows, cols) of the area the tree grows in
+    fn tree_size(&self) -> (i32, i32) {
+        (self.layout.tree_rows, self.layout.tree_cols)
+    }
 
-        if life < 4 {
-            branch_type = BranchType::Dying;
+    fn draw_wins(&mut self) {
+        let mut rows = 0;
+        let mut cols = 0;
+        if self.config.headless {
+            (cols, rows) = (self.config.canvas_width, self.config.canvas_height);
+        } else {
+            getmaxyx(stdscr(), &mut rows, &mut cols);
         }
 
-        match branch_type {
-            BranchType::Trunk => {
-                if dy == 0 {
-                    "/~"
-                } else if dx < 0 {
-                    "\\|"
-                } else if dx == 0 {
-                    "/|\\"
-                } else if dx > 0 {
-                    "|/"
-                } else {
-                    fallback_char
-                }
-            }
-            BranchType::ShootLeft => {
-                if dy > 0 {
-                    "\\"
-                } else if dy == 0 {
-                    "\\_"
-                } else if dx < 0 {
-                    "\\|"
-                } else if dx == 0 {
-                    "/|"
-                } else if dx > 0 {
-                    "/"
-                } else {
-                    fallback_char
-                }
+        // calculate where base should go, unless count_steps already did for this screen
+        let planned = self
+            .planned
+            .take()
+            .filter(|layout| (layout.rows, layout.cols) == (rows, cols));
+        let Some(layout) = planned.or_else(|| Layout::new(&self.config, rows, cols)) else {
+            self.too_small(rows, cols);
+        };
+        self.layout = layout;
+        self.forest = if self.config.forest > 1 {
+            let Some(forest) = forest::plant(&self.config, rows, cols) else {
+                self.too_small(rows, cols);
+            };
+            forest
+        } else {
+            Vec::new()
+        };
+        let (base_width, base_height) = base_size(&layout.base_type);
+
+        self.canvas = Canvas::new(cols, rows);
+
+        if !self.config.headless {
+            // clean up old objects
+            self.del_wins();
+
+            // the sky goes under everything, draw_sky lays the tree over it
+            self.sky = Sky::new(self.config.weather, rows, cols, self.config.seed);
+            if self.sky.is_some() {
+                self.objects.sky_win = newwin(rows, cols, 0, 0);
+                self.objects.sky_panel = new_panel(self.objects.sky_win);
             }
-            BranchType::ShootRight => {
-                if dy > 0 {
-                    "/"
-                } else if dy == 0 {
-                    "_/"
-                } else if dx < 0 {
-                    "\\|"
-                } else if dx == 0 {
-                    "/|"
-                } else if dx > 0 {
-                    "/"
-                } else {
-                    fallback_char
-                }
+
+            // create windows
+            self.objects.base_win = newwin(base_height, base_width, layout.base_y, layout.base_x);
+            // the tree window spans the screen, a forest draws its bases on it too
+            let tree_rows = if self.forest.is_empty() {
+                layout.tree_rows
+            } else {
+                rows
+            };
+            self.objects.tree_win = newwin(tree_rows, cols, 0, 0);
+
+            // // create tree and base panels
+            self.objects.base_panel = new_panel(self.objects.base_win);
+            self.objects.tree_panel = new_panel(self.objects.tree_win);
+            if self.sky.is_some() {
+                hide_panel(self.objects.tree_panel);
             }
-            BranchType::Dying | BranchType::Dead => {
-                if self.config.leaves_size > 0 {
-                    &self.config.leaves[..=(dice(self.config.leaves_size) as usize)]
-                } else {
-                    ""
-                }
+
+            self.objects.status_win = newwin(1, 1, 0, 0);
+            self.objects.status_panel = new_panel(self.objects.status_win);
+            hide_panel(self.objects.status_panel);
+            self.draw_status();
+            if !self.objects.help_panel.is_null() {
+                top_panel(self.objects.help_panel);
             }
         }
-    }
 
-    fn draw_wins(&mut self) {
-        let mut rows = 0;
-        let mut cols = 0;
+        self.draw_base();
+    }
 
-        let (base_width, base_height) = match self.config.base_type {
-            BaseType::None => (0, 0),
-            BaseType::Small => (15, 3),
-            BaseType::Big => (31, 4),
+    // leave with a message rather than grow a tree nobody can see
+    fn too_small(&self, rows: i32, cols: i32) -> ! {
+        let screen = if self.config.headless {
+            "canvas"
+        } else {
+            endwin();
+            guard::disarm();
+            "terminal"
         };
+        eprintln!(
+            "{screen} too small for a bonsai: {cols}x{rows}, needs at least {MIN_COLS}x{MIN_ROWS}"
+        );
+        std::process::exit(1);
+    }
 
-        // calculate where base should go
-        getmaxyx(stdscr(), &mut rows, &mut cols);
-        let base_origin_y = rows - base_height;
-        let base_origin_x = (cols / 2) - (base_width / 2);
+    // the message in a box to the lower right of the tree, redrawn whenever it changes
+    fn draw_message(&mut self) {
+        self.overlay = Canvas::new(self.canvas.width, self.canvas.height);
+        if !self.objects.message_panel.is_null() {
+            del_panel(self.objects.message_border_panel);
+            del_panel(self.objects.message_panel);
+            delwin(self.objects.message_border_win);
+            delwin(self.objects.message_win);
+            self.objects.message_border_panel = NULL;
+            self.objects.message_panel = NULL;
+            self.objects.message_border_win = NULL;
+            self.objects.message_win = NULL;
+        }
+        if self.config.message.is_empty() {
+            return;
+        }
 
-        // clean up old objects
-        // del_objects(objects);
+        let lines = wrap(&self.config.message, self.layout.message_width());
+        let width = lines.iter().map(|line| line.width()).max().unwrap_or(0) as i32;
+        let height = lines.len() as i32;
+        let (y, x) = self.layout.message_at(width, height);
+
+        const BORDER: Attr = Attr::new(8, true);
+        const TEXT: Attr = Attr::new(7, false);
+        let edge = format!("+{}+", "-".repeat(width as usize + 2));
+        let inside = " ".repeat(width as usize + 2);
+        self.overlay.put_str(y, x, &edge, BORDER);
+        self.overlay.put_str(y + height + 1, x, &edge, BORDER);
+        for row in y + 1..=y + height {
+            self.overlay.put_str(row, x, "|", BORDER);
+            self.overlay.put_str(row, x + 1, &inside, TEXT);
+            self.overlay.put_str(row, x + width + 3, "|", BORDER);
+        }
+        for (row, line) in (y + 1..).zip(&lines) {
+            self.overlay.put_str(row, x + 2, line, TEXT);
+        }
+        if self.config.headless {
+            return;
+        }
 
-        // create windows
-        self.objects.base_win = newwin(base_height, base_width, base_origin_y, base_origin_x);
-        self.objects.tree_win = newwin(rows - base_height, cols, 0, 0);
+        self.objects.message_border_win = newwin(height + 2, width + 4, y, x);
+        self.objects.message_win = newwin(height, width + 1, y + 1, x + 2);
+        wattrset(self.objects.message_border_win, BORDER.to_curses());
+        mvwprintw(self.objects.message_border_win, 0, 0, &edge);
+        mvwprintw(self.objects.message_border_win, height + 1, 0, &edge);
+        for row in 1..=height {
+            mvwprintw(self.objects.message_border_win, row, 0, "|");
+            mvwprintw(self.objects.message_border_win, row, width + 3, "|");
+        }
+        // addstr, the message is not a format string
+        for (row, line) in (0..).zip(&lines) {
+            mvwaddstr(self.objects.message_win, row, 0, line);
+        }
+        self.objects.message_border_panel = new_panel(self.objects.message_border_win);
+        self.objects.message_panel = new_panel(self.objects.message_win);
+        if !self.objects.help_panel.is_null() {
+            top_panel(self.objects.help_panel);
+        }
+    }
 
-        // // create tree and base panels
-        self.objects.base_panel = new_panel(self.objects.base_win);
-        self.objects.tree_panel = new_panel(self.objects.tree_win);
+    // what is on screen: the tree and its base with the message box on top
+    fn screen(&self) -> Canvas {
+        let mut screen = self.canvas.clone();
+        screen.paste(0, 0, &self.overlay);
+        screen
+    }
 
-        self.draw_base();
+    // free the windows and panels of the last tree before making new ones. until the first
+    // tree they are stdscr, which is not ours to free
+    fn del_wins(&mut self) {
+        let screen = stdscr();
+        let objects = &mut self.objects;
+        for (panel, win) in [
+            (&mut objects.base_panel, &mut objects.base_win),
+            (&mut objects.tree_panel, &mut objects.tree_win),
+            (&mut objects.status_panel, &mut objects.status_win),
+            (&mut objects.sky_panel, &mut objects.sky_win),
+        ] {
+            if !panel.is_null() && *panel != screen {
+                del_panel(*panel);
+            }
+            if !win.is_null() && *win != screen {
+                delwin(*win);
+            }
+            (*panel, *win) = (NULL, NULL);
+        }
     }
 
-    fn draw_message(&self) {
-        if self.config.message.is_empty() {
+    fn draw_base(&mut self) {
+        if self.forest.is_empty() {
+            self.draw_base_in(self.layout, self.objects.base_win, (0, 0));
             return;
         }
-        todo!();
-    }
-
-    fn del_objects(objects: &NcursesObjects) {
-        // this seg faults
-        del_panel(objects.base_panel);
-        del_panel(objects.tree_panel);
-        del_panel(objects.message_border_panel);
-        del_panel(objects.message_panel);
-
-        delwin(objects.base_win);
-        delwin(objects.tree_win);
-        delwin(objects.message_border_win);
-        delwin(objects.message_win);
-    }
-
-    fn draw_base(&self) {
-        match self.config.base_type {
-            BaseType::None => {}
-            BaseType::Small => {
-                wattron(self.objects.base_win, COLOR_PAIR(8));
-                wprintw(self.objects.base_win, "(");
-                wattron(self.objects.base_win, COLOR_PAIR(2));
-                wprintw(self.objects.base_win, "---");
-                wattron(self.objects.base_win, COLOR_PAIR(11));
-                wprintw(self.objects.base_win, "./~~~\\.");
-                wattron(self.objects.base_win, COLOR_PAIR(2));
-                wprintw(self.objects.base_win, "---");
-                wattron(self.objects.base_win, COLOR_PAIR(8));
-                wprintw(self.objects.base_win, ")");
-
-                mvwprintw(self.objects.base_win, 1, 0, " (           ) ");
-                mvwprintw(self.objects.base_win, 2, 0, "  (_________)  ");
+
+        let (tree_win, (rows, cols)) = (
+            self.objects.tree_win,
+            (self.canvas.height, self.canvas.width),
+        );
+        let layouts: Vec<Layout> = self.forest.iter().map(|planting| planting.layout).collect();
+        for layout in layouts {
+            self.draw_base_in(layout, tree_win, (layout.base_y, layout.base_x));
+        }
+        if self.config.ground {
+            let ground = ground(cols);
+            self.canvas.put_str(rows - 1, 0, &ground, GROUND);
+            if !self.config.headless {
+                wattrset(tree_win, GROUND.to_curses());
+                mvwprintw(tree_win, rows - 1, 0, &ground);
             }
-            BaseType::Big => {
-                wattron(self.objects.base_win, A_BOLD() | COLOR_PAIR(8));
-                wprintw(self.objects.base_win, ":");
-                wattron(self.objects.base_win, COLOR_PAIR(2));
-                wprintw(self.objects.base_win, "___________");
-                wattron(self.objects.base_win, COLOR_PAIR(11));
-                wprintw(self.objects.base_win, "./~~~\\.");
-                wattron(self.objects.base_win, COLOR_PAIR(2));
-                wprintw(self.objects.base_win, "___________");
-                wattron(self.objects.base_win, COLOR_PAIR(8));
-                wprintw(self.objects.base_win, ":");
-
-                mvwprintw(
-                    self.objects.base_win,
-                    1,
-                    0,
-                    " \\                           / ",
-                );
-                mvwprintw(
-                    self.objects.base_win,
-                    2,
-                    0,
-                    "  \\_________________________/ ",
-                );
-                mvwprintw(self.objects.base_win, 3, 0, "  (_)                     (_)");
+        }
+    }
 
-                wattroff(self.objects.base_win, A_BOLD());
+    // draw the base of layout onto the canvas, and onto win with the base corner at origin
+    fn draw_base_in(&mut self, layout: Layout, win: WINDOW, (origin_y, origin_x): (i32, i32)) {
+        for &(y, x, text, attr) in base_art(&layout.base_type) {
+            self.canvas
+                .put_str(layout.base_y + y, layout.base_x + x, text, attr);
+            if !self.config.headless {
+                wattrset(win, attr.to_curses());
+                mvwprintw(win, origin_y + y, origin_x + x, text);
             }
         }
     }
 
-    // check for key press
-    fn check_key_press(&self) -> bool {
-        if (self.config.screensaver && wgetch(stdscr()) != ERR) || (wgetch(stdscr()) == 'q' as i32)
-        {
-            self.finish();
-            true
-        } else {
-            false
+    // leave once a signal was caught, restoring the terminal. ctrl-c saves like quitting
+    // does, other signals just leave
+    fn exit_if_caught(&mut self) {
+        let Some(signal) = guard::caught() else {
+            return;
+        };
+        if signal != libc::SIGINT {
+            self.config.save = false;
         }
+        self.finish();
+        std::process::exit(128 + signal);
     }
 
     fn finish(&self) {
         clear();
         refresh();
         endwin();
+        guard::disarm();
         if self.config.save {
-            // saveToFile
+            if let Err(why) = self.save_progress() {
+                eprintln!("{why}");
+            }
+        }
+    }
+
+    fn save_progress(&self) -> anyhow::Result<()> {
+        // as it grew, so a fitted tree regrows the same without fitting again
+        progress::save(
+            &self.config.save_file,
+            &Saved {
+                seed: self.layout.seed,
+                branches: self.branches(),
+                cuts: self.grower.topology.cuts().to_vec(),
+                growth: Some((self.layout.life_start, self.layout.multiplier)),
+            },
+        )
+    }
+
+    // grow the garden tree for the time since it was last opened. the growth since the last
+    // visit is shown live, what was already there regrows instantly like a loaded tree
+    fn open_garden(&mut self) {
+        let now = garden::now();
+        let file = &self.config.garden_file;
+        let mut garden = match Garden::load(file) {
+            Ok(Some(garden)) => garden,
+            Ok(None) => Garden::plant(self.config.seed, now),
+            Err(why) => {
+                eprintln!("{why}");
+                std::process::exit(1);
+            }
+        };
+        garden.visit(now);
+
+        // the garden keeps its own seed and growth, the save file is left alone
+        self.config.seed = garden.seed;
+        self.config.load = false;
+        self.config.target_branch_count = garden.seen;
+        self.config.branch_limit = garden.branches();
+        self.config.wilt = garden.wilt(now);
+        self.notice = format!(
+            "planted {}, watered {}",
+            garden::days_ago(garden.planted, now),
+            garden::days_ago(garden.watered, now)
+        );
+        if self.config.wilt != Wilt::Healthy {
+            self.notice += " - run `rustbonsai water`";
+        }
+
+        garden.seen = garden.branches();
+        if let Err(why) = garden.save(file) {
+            eprintln!("{why}");
+            std::process::exit(1);
+        }
+    }
+
+    fn load_progress(&mut self) {
+        match progress::load(&self.config.load_file) {
+            Ok(saved) => {
+                self.config.seed = saved.seed;
+                self.config.target_branch_count = saved.branches;
+                self.config.cuts = saved.cuts;
+                self.config.growth = saved.growth;
+            }
+            // nothing has been saved yet the first time a screensaver runs
+            Err(_) if self.config.screensaver => {}
+            Err(why) => eprintln!("{why}"),
         }
     }
 }
 
+// greedy word wrap in terminal columns, splitting words longer than a line
+fn wrap(text: &str, width: i32) -> Vec<String> {
+    let width = width.max(1) as usize;
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        for chunk in chunks(word, width) {
+            if !line.is_empty() && line.width() + 1 + chunk.width() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &chunk;
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// a word in pieces at most width columns wide, wide characters take two
+fn chunks(word: &str, width: usize) -> Vec<String> {
+    let mut chunks = vec![String::new()];
+    for ch in word.chars() {
+        let chunk = chunks.last_mut().unwrap();
+        if !chunk.is_empty() && chunk.width() + ch.width().unwrap_or(0) > width {
+            chunks.push(String::new());
+        }
+        chunks.last_mut().unwrap().push(ch);
+    }
+    chunks
+}
+
 fn update_screen(time_step: u64) {
     update_panels();
     doupdate();
@@ -475,9 +801,26 @@ fn update_screen(time_step: u64) {
         sleep(Duration::from_millis(time_step));
     }
 }
-fn dice(sides: i32) -> i32 {
-    rand() % sides
+fn water_garden(file: &str) {
+    let result = match Garden::load(file) {
+        Ok(Some(mut garden)) => {
+            garden.water(garden::now());
+            garden.save(file)
+        }
+        Ok(None) => Err(anyhow::anyhow!(
+            "nothing is planted yet, run `rustbonsai garden` first"
+        )),
+        Err(why) => Err(why),
+    };
+    match result {
+        Ok(()) => println!("watered your bonsai"),
+        Err(why) => {
+            eprintln!("{why}");
+            std::process::exit(1);
+        }
+    }
 }
-fn rand() -> i32 {
-    RNG.get().unwrap().lock().unwrap().gen::<i32>().abs()
+
+fn new_seed() -> u64 {
+    RNG.get().unwrap().lock().unwrap().gen()
 }
@@ -0,0 +1,82 @@
+use crate::curses::{initscr, newterm_tty, NULL, PANEL, WINDOW};
+
+pub struct NcursesObjects {
+    pub base_win: WINDOW,
+    pub tree_win: WINDOW,
+    // null until a message is shown
+    pub message_border_win: WINDOW,
+    pub message_win: WINDOW,
+    pub status_win: WINDOW,
+    pub help_win: WINDOW,
+    // null while the view of a tree bigger than the screen is at home
+    pub view_win: WINDOW,
+    // null without a sky behind the tree
+    pub sky_win: WINDOW,
+
+    pub base_panel: PANEL,
+    pub tree_panel: PANEL,
+    pub message_border_panel: PANEL,
+    pub message_panel: PANEL,
+    pub status_panel: PANEL,
+    // null while the help overlay is closed
+    pub help_panel: PANEL,
+    pub view_panel: PANEL,
+    pub sky_panel: PANEL,
+}
+
+impl Default for NcursesObjects {
+    fn default() -> Self {
+        Self::on_screen(initscr())
+    }
+}
+
+impl NcursesObjects {
+    // curses on /dev/tty instead of stdin and stdout, leaving stdin free for piped input
+    pub fn on_tty() -> Self {
+        Self::on_screen(newterm_tty())
+    }
+
+    // windows and panels are placeholders until draw_wins creates them
+    fn on_screen(screen: WINDOW) -> Self {
+        Self {
+            base_win: screen,
+            tree_win: screen,
+            message_border_win: NULL,
+            message_win: NULL,
+            status_win: screen,
+            help_win: NULL,
+            view_win: NULL,
+            sky_win: NULL,
+            base_panel: screen,
+            tree_panel: screen,
+            message_border_panel: NULL,
+            message_panel: NULL,
+            status_panel: screen,
+            help_panel: NULL,
+            view_panel: NULL,
+            sky_panel: NULL,
+        }
+    }
+
+    // placeholder objects for rendering without a terminal, never passed to curses
+    pub fn headless() -> Self {
+        Self {
+            base_win: NULL,
+            tree_win: NULL,
+            message_border_win: NULL,
+            message_win: NULL,
+            status_win: NULL,
+            help_win: NULL,
+            view_win: NULL,
+            sky_win: NULL,
+            base_panel: NULL,
+            tree_panel: NULL,
+            message_border_panel: NULL,
+            message_panel: NULL,
+            status_panel: NULL,
+            help_panel: NULL,
+            view_panel: NULL,
+            sky_panel: NULL,
+        }
+    }
+}
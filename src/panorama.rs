@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use crate::curses::{getmaxyx, hide_panel, new_panel, newwin, stdscr};
+
+use rustbonsai::{
+    domain::{
+        keymap::Action,
+        layout::{MIN_COLS, MIN_ROWS},
+    },
+    landscape::Landscape,
+};
+
+use crate::{new_seed, update_screen, view::draw_canvas, Tree};
+
+impl Tree {
+    // pan across an endless landscape a column every time_step, until a key ends it
+    pub(crate) fn run_panorama(&mut self) {
+        self.init_terminal();
+        let mut rows = 0;
+        let mut cols = 0;
+        getmaxyx(stdscr(), &mut rows, &mut cols);
+        if rows < MIN_ROWS || cols < MIN_COLS {
+            self.too_small(rows, cols);
+        }
+
+        self.objects.tree_win = newwin(rows, cols, 0, 0);
+        self.objects.tree_panel = new_panel(self.objects.tree_win);
+        self.objects.status_win = newwin(1, 1, 0, 0);
+        self.objects.status_panel = new_panel(self.objects.status_win);
+        hide_panel(self.objects.status_panel);
+        self.draw_status();
+
+        let mut landscape = Landscape::new(&self.config, rows, cols);
+        loop {
+            self.canvas = landscape.view();
+            draw_canvas(self.objects.tree_win, &self.canvas);
+            update_screen(0);
+
+            let step = Duration::from_millis(self.config.time_step);
+            match self.wait_for_action(Some(step)) {
+                None => landscape.advance(),
+                Some(Action::NewTree) => {
+                    self.config.seed = new_seed();
+                    landscape = Landscape::new(&self.config, rows, cols);
+                }
+                Some(Action::Regrow) => landscape = Landscape::new(&self.config, rows, cols),
+                Some(_) => break,
+            }
+        }
+    }
+}
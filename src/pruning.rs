@@ -0,0 +1,150 @@
+use crate::curses::{
+    doupdate, keypad, mvwprintw, stdscr, update_panels, wattrset, werase, wgetch, wtimeout,
+    ToCurses, A_REVERSE, KEY_ENTER,
+};
+
+use rustbonsai::domain::{
+    canvas::Attr,
+    keymap::{key_name, Action},
+    layout::clip,
+};
+
+use crate::{guard, Tree};
+
+const ESCAPE: i32 = 27;
+
+impl Tree {
+    // move a cursor over the finished tree and cut off the branch under it
+    pub(crate) fn prune(&mut self) {
+        keypad(stdscr(), true);
+        // the cursor moves over the screen as it was grown
+        self.hide_view();
+        let (rows, cols) = self.tree_size();
+        let (mut y, mut x) = self
+            .grower
+            .topology
+            .drawn()
+            .next()
+            .map_or((rows - 1, cols / 2), |(_, segment)| (segment.y, segment.x));
+
+        loop {
+            let selected = self.grower.topology.branch_at(y, x);
+            self.redraw_tree(selected, Some((y, x)));
+            let keys = format!(
+                "{} undo  {} redo  {} save  {} done",
+                self.key_for(Action::Undo),
+                self.key_for(Action::Redo),
+                self.key_for(Action::Save),
+                self.key_for(Action::Quit)
+            );
+            self.notice = match selected {
+                Some(id) => format!(
+                    "branch {id} ({:?})  {} cut  {keys}",
+                    self.grower.topology.branches[id].branch_type,
+                    self.key_for(Action::Cut)
+                ),
+                None => format!(
+                    "{}/{}/{}/{} move  {keys}",
+                    self.key_for(Action::PanUp),
+                    self.key_for(Action::PanDown),
+                    self.key_for(Action::PanLeft),
+                    self.key_for(Action::PanRight)
+                ),
+            };
+            self.draw_status();
+            update_panels();
+            doupdate();
+
+            wtimeout(stdscr(), -1);
+            let key = wgetch(stdscr());
+            if guard::caught().is_some() {
+                break;
+            }
+            // enter and escape cant be rebound, so they always cut and finish
+            let action = match key {
+                KEY_ENTER => Some(Action::Cut),
+                key if key == '\n' as i32 => Some(Action::Cut),
+                ESCAPE => Some(Action::Quit),
+                key => self.config.keymap.action(key),
+            };
+            match action {
+                Some(Action::PanUp) => y -= 1,
+                Some(Action::PanDown) => y += 1,
+                Some(Action::PanLeft) => x -= 1,
+                Some(Action::PanRight) => x += 1,
+                Some(Action::Cut) => self.cut(selected),
+                Some(Action::Undo) => _ = self.grower.topology.undo(),
+                Some(Action::Redo) => _ = self.grower.topology.redo(),
+                Some(Action::Save) => {
+                    if let Err(why) = self.save_progress() {
+                        self.notice = why.to_string();
+                    }
+                }
+                Some(Action::Quit) => break,
+                _ => {}
+            }
+            y = y.clamp(0, rows - 1);
+            x = x.clamp(0, cols - 1);
+        }
+
+        self.notice.clear();
+        self.redraw_tree(None, None);
+        self.draw_status();
+        update_panels();
+        doupdate();
+    }
+
+    // name of the key bound to an action, for the status line
+    fn key_for(&self, action: Action) -> String {
+        self.config
+            .keymap
+            .key(action)
+            .map_or_else(String::new, key_name)
+    }
+
+    fn cut(&mut self, selected: Option<usize>) {
+        if let Some(id) = selected {
+            self.grower.topology.cut(id);
+        }
+    }
+
+    // draw the tree again from its topology, leaving out pruned branches.
+    // the selected branch and its offshoots are highlighted, an empty cursor cell gets a marker
+    pub(crate) fn redraw_tree(&mut self, selected: Option<usize>, cursor: Option<(i32, i32)>) {
+        let (rows, cols) = self.tree_size();
+        self.canvas.clear_top(rows);
+        if !self.config.headless {
+            werase(self.objects.tree_win);
+        }
+
+        for (id, segment) in self.grower.topology.drawn() {
+            // curses draws nothing of text that starts off the window
+            let Some((x, text)) = clip(segment.y, segment.x, &segment.text, rows, cols) else {
+                continue;
+            };
+            self.canvas
+                .put_str(segment.y, self.layout.tree_x + x, &text, segment.attr);
+            if !self.config.headless {
+                let highlight =
+                    selected.is_some_and(|branch| self.grower.topology.is_descendant(id, branch));
+                let attr = if highlight {
+                    segment.attr.to_curses() | A_REVERSE()
+                } else {
+                    segment.attr.to_curses()
+                };
+                wattrset(self.objects.tree_win, attr);
+                mvwprintw(
+                    self.objects.tree_win,
+                    segment.y,
+                    self.layout.tree_x + x,
+                    &text,
+                );
+            }
+        }
+
+        if let (Some((y, x)), None, false) = (cursor, selected, self.config.headless) {
+            wattrset(self.objects.tree_win, Attr::new(8, true).to_curses());
+            mvwprintw(self.objects.tree_win, y, self.layout.tree_x + x, "+");
+        }
+    }
+}
@@ -1,28 +1,39 @@
-use crate::{dice, domain::branch_type::BranchType, rand};
+use rand::rngs::StdRng;
 
-pub fn set_deltas(branch_type: BranchType, life: i32, age: i32, multiplier: i32) -> (i32, i32) {
+use crate::{
+    domain::branch_type::BranchType,
+    grower::{dice, rand},
+};
+
+pub fn set_deltas(
+    rng: &mut StdRng,
+    branch_type: BranchType,
+    life: i32,
+    age: i32,
+    multiplier: i32,
+) -> (i32, i32) {
     let mut dx = 0;
     let mut dy = 0;
     match branch_type {
         BranchType::Trunk => {
-            set_delta_trunk(age, life, &mut dy, &mut dx, multiplier);
+            set_delta_trunk(rng, age, life, &mut dy, &mut dx, multiplier);
         }
         BranchType::ShootLeft | BranchType::ShootRight => {
-            set_delta_shoot(&mut dy, &mut dx, branch_type);
+            set_delta_shoot(rng, &mut dy, &mut dx, branch_type);
         }
         BranchType::Dying => {
-            set_deltas_dying(&mut dy, &mut dx);
+            set_deltas_dying(rng, &mut dy, &mut dx);
         }
         BranchType::Dead => {
-            set_deltas_dead(&mut dy, &mut dx);
+            set_deltas_dead(rng, &mut dy, &mut dx);
         }
     }
     (dx, dy)
 }
 
-fn set_deltas_dead(dy: &mut i32, dx: &mut i32) {
+fn set_deltas_dead(rng: &mut StdRng, dy: &mut i32, dx: &mut i32) {
     // dead: fill in surrounding area
-    let roll = dice(10);
+    let roll = dice(rng, 10);
     if (0..=2).contains(&roll) {
         *dy = -1;
     } else if (3..=6).contains(&roll) {
@@ -30,12 +41,12 @@ fn set_deltas_dead(dy: &mut i32, dx: &mut i32) {
     } else if (7..=9).contains(&roll) {
         *dy = 1;
     }
-    *dx = (dice(3)) - 1;
+    *dx = (dice(rng, 3)) - 1;
 }
 
-fn set_deltas_dying(dy: &mut i32, dx: &mut i32) {
+fn set_deltas_dying(rng: &mut StdRng, dy: &mut i32, dx: &mut i32) {
     // dying: discourage vertical growth(?); trend left/right (-3,3)
-    let roll = dice(10);
+    let roll = dice(rng, 10);
     if (0..=1).contains(&roll) {
         *dy = -1;
     } else if (2..=8).contains(&roll) {
@@ -44,7 +55,7 @@ fn set_deltas_dying(dy: &mut i32, dx: &mut i32) {
         *dy = 1;
     }
 
-    let roll = dice(15);
+    let roll = dice(rng, 15);
     if roll == 0 {
         *dx = -3;
     } else if (1..=2).contains(&roll) {
@@ -62,9 +73,9 @@ fn set_deltas_dying(dy: &mut i32, dx: &mut i32) {
     }
 }
 
-fn set_delta_shoot(dy: &mut i32, dx: &mut i32, branch_type: BranchType) {
+fn set_delta_shoot(rng: &mut StdRng, dy: &mut i32, dx: &mut i32, branch_type: BranchType) {
     // left shoot: trend left and little vertical movement
-    let roll = dice(10);
+    let roll = dice(rng, 10);
     if (0..=1).contains(&roll) {
         *dy = -1;
     } else if (2..=7).contains(&roll) {
@@ -73,7 +84,7 @@ fn set_delta_shoot(dy: &mut i32, dx: &mut i32, branch_type: BranchType) {
         *dy = 1;
     }
 
-    let roll = dice(10);
+    let roll = dice(rng, 10);
     if (0..=1).contains(&roll) {
         *dx = -2;
     } else if (2..=5).contains(&roll) {
@@ -89,11 +100,18 @@ fn set_delta_shoot(dy: &mut i32, dx: &mut i32, branch_type: BranchType) {
     }
 }
 
-fn set_delta_trunk(age: i32, life: i32, dy: &mut i32, dx: &mut i32, multiplier: i32) {
+fn set_delta_trunk(
+    rng: &mut StdRng,
+    age: i32,
+    life: i32,
+    dy: &mut i32,
+    dx: &mut i32,
+    multiplier: i32,
+) {
     // new or dead trunk
     if age <= 2 || life < 4 {
         *dy = 0;
-        *dx = (rand() % 3) - 1;
+        *dx = (rand(rng) % 3) - 1;
     }
     // young trunk should grow wide
     else if age < multiplier * 3 {
@@ -104,7 +122,7 @@ fn set_delta_trunk(age: i32, life: i32, dy: &mut i32, dx: &mut i32, multiplier:
             *dy = 0;
         }
 
-        let roll = dice(10);
+        let roll = dice(rng, 10);
         if roll == 0 {
             *dx = -2;
         } else if (1..=3).contains(&roll) {
@@ -119,12 +137,12 @@ fn set_delta_trunk(age: i32, life: i32, dy: &mut i32, dx: &mut i32, multiplier:
     }
     // middle-aged trunk
     else {
-        let roll = dice(10);
+        let roll = dice(rng, 10);
         if roll > 2 {
             *dy = -1;
         } else {
             *dy = 0;
         }
-        *dx = (dice(3)) - 1;
+        *dx = (dice(rng, 3)) - 1;
     }
 }
@@ -0,0 +1,27 @@
+use std::time::Instant;
+
+use rustbonsai::domain::sky::TICK;
+
+use crate::{curses::overlay, view::draw_canvas, Tree};
+
+impl Tree {
+    // move the sky along when a tick is due, then lay the tree window over it. the sky panel
+    // sits under the tree panel, which stays hidden as curses panels are not see-through
+    pub(crate) fn draw_sky(&mut self) {
+        let Some(sky) = &mut self.sky else {
+            return;
+        };
+        let now = Instant::now();
+        if now >= self.sky_due {
+            sky.tick(&self.canvas);
+            // a sky held up by a pause carries on from now rather than catch up
+            self.sky_due = if now.duration_since(self.sky_due) > TICK {
+                now + TICK
+            } else {
+                self.sky_due + TICK
+            };
+        }
+        draw_canvas(self.objects.sky_win, &sky.frame());
+        overlay(self.objects.tree_win, self.objects.sky_win);
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use rustbonsai::domain::{
+    branch_type::BranchType, canvas::Canvas, falling::FallingLeaves, keymap::Action,
+    topology::Topology, transition::Transition,
+};
+
+use crate::{update_screen, view::draw_canvas, Tree};
+
+const FRAME: Duration = Duration::from_millis(40);
+
+impl Tree {
+    // wait out time_wait before the next tree, spending the end of it on the transition. a key
+    // pressed on the way cuts the wait short
+    pub(crate) fn wait_and_leave(&mut self, time_wait: Duration) -> Option<Action> {
+        let length = match self.config.transition {
+            Transition::Cut => Duration::ZERO,
+            _ => self.config.transition_time.min(time_wait),
+        };
+        if let Some(action) = self.idle(Some(time_wait - length)) {
+            return Some(action);
+        }
+
+        self.hide_view();
+        // from the tree as it is on screen, without the leaves that already fell
+        let tree = self
+            .falling
+            .as_ref()
+            .map_or_else(|| self.grown(), FallingLeaves::frame);
+        let leaves = self.leaves();
+
+        // counted in frames rather than by the clock, so pausing holds the transition too
+        let frames = (length.as_millis() / FRAME.as_millis()) as u32;
+        for frame in 0..frames {
+            let progress = f64::from(frame) / f64::from(frames);
+            let shown = self
+                .config
+                .transition
+                .frame(&tree, &leaves, progress, self.config.seed);
+            draw_canvas(self.objects.tree_win, &shown);
+            self.draw_sky();
+            update_screen(0);
+            if let Some(action) = self.wait_for_action(Some(FRAME)) {
+                return Some(action);
+            }
+        }
+        None
+    }
+
+    // what of the canvas is in the tree window, the tree without its base
+    pub(crate) fn grown(&self) -> Canvas {
+        let rows = if self.forest.is_empty() {
+            self.layout.tree_rows
+        } else {
+            self.canvas.height
+        };
+        let mut tree = Canvas::new(self.canvas.width, rows);
+        tree.paste(0, 0, &self.canvas);
+        tree
+    }
+
+    // the leaves of the tree, or of every tree of a forest, where they are on the canvas
+    pub(crate) fn leaves(&self) -> Canvas {
+        let mut leaves = Canvas::new(self.canvas.width, self.canvas.height);
+        let trees = std::iter::once((&self.grower.topology, self.layout.tree_x)).chain(
+            self.forest
+                .iter()
+                .map(|planting| (&planting.grower.topology, planting.layout.tree_x)),
+        );
+        for (topology, tree_x) in trees {
+            mark_leaves(&mut leaves, topology, tree_x);
+        }
+        leaves
+    }
+}
+
+fn mark_leaves(leaves: &mut Canvas, topology: &Topology, tree_x: i32) {
+    for (id, segment) in topology.drawn() {
+        if matches!(
+            topology.branches[id].branch_type,
+            BranchType::Dying | BranchType::Dead
+        ) {
+            leaves.put_str(segment.y, tree_x + segment.x, &segment.text, segment.attr);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::curses::{
+    del_panel, delwin, getmaxyx, mvwaddstr, new_panel, newwin, stdscr, top_panel, wattrset, werase,
+    ToCurses, NULL, WINDOW,
+};
+
+use rustbonsai::domain::{canvas::Canvas, keymap::Action, viewport::Viewport};
+
+use crate::Tree;
+
+// how far one press of an arrow key pans, in screen cells
+const PAN_ROWS: i32 = 2;
+const PAN_COLS: i32 = 4;
+
+impl Tree {
+    // the whole tree with its base and message, however far it grew past the screen, and
+    // where the corner of the screen is on it
+    pub(crate) fn world(&self) -> (Canvas, (i32, i32)) {
+        let screen = self.screen();
+        let tree_x = self.layout.tree_x;
+        let (top, left, bottom, right) = self.grower.bounds().map_or(
+            (0, 0, screen.height - 1, screen.width - 1),
+            |(top, left, bottom, right)| {
+                (
+                    top.min(0),
+                    (tree_x + left).min(0),
+                    bottom.max(screen.height - 1),
+                    (tree_x + right).max(screen.width - 1),
+                )
+            },
+        );
+        let home = (-top, -left);
+
+        let mut world = Canvas::new(right - left + 1, bottom - top + 1);
+        for (_, segment) in self.grower.topology.drawn() {
+            world.put_str(
+                home.0 + segment.y,
+                home.1 + tree_x + segment.x,
+                &segment.text,
+                segment.attr,
+            );
+        }
+        // what is on screen wins, so the message stays on top of the tree
+        world.paste(home.0, home.1, &screen);
+        (world, home)
+    }
+
+    // where the corner of the tree area is on the picture
+    pub(crate) fn origin(&self) -> (i32, i32) {
+        let (top, left) = if self.config.scroll {
+            self.world().1
+        } else {
+            (0, 0)
+        };
+        (top, left + self.layout.tree_x)
+    }
+
+    // what outputs get: the screen, or with --scroll the whole tree
+    pub(crate) fn picture(&self) -> Canvas {
+        if self.config.scroll {
+            self.world().0
+        } else {
+            self.screen()
+        }
+    }
+
+    pub(crate) fn move_view(&mut self, action: Action) {
+        let (rows, cols) = screen_size();
+        match action {
+            Action::PanUp => self.viewport.pan(-PAN_ROWS, 0),
+            Action::PanDown => self.viewport.pan(PAN_ROWS, 0),
+            Action::PanLeft => self.viewport.pan(0, -PAN_COLS),
+            Action::PanRight => self.viewport.pan(0, PAN_COLS),
+            Action::ZoomOut => self.viewport.zoom(1, rows, cols),
+            Action::ZoomIn => self.viewport.zoom(-1, rows, cols),
+            _ => return,
+        }
+        self.show_view();
+    }
+
+    // cover the screen with the view while it is away from home, uncover it once back
+    pub(crate) fn show_view(&mut self) {
+        let (world, home) = self.world();
+        let (rows, cols) = screen_size();
+        self.viewport.clamp(&world, home, rows, cols);
+        if self.viewport.is_home() {
+            self.hide_view();
+            return;
+        }
+
+        if self.objects.view_panel.is_null() {
+            self.objects.view_win = newwin(rows, cols, 0, 0);
+            self.objects.view_panel = new_panel(self.objects.view_win);
+        }
+        let view = self.viewport.render(&world, home, rows, cols);
+        draw_canvas(self.objects.view_win, &view);
+        top_panel(self.objects.view_panel);
+        if !self.objects.help_panel.is_null() {
+            top_panel(self.objects.help_panel);
+        }
+        self.draw_status();
+    }
+
+    pub(crate) fn hide_view(&mut self) {
+        self.viewport = Viewport::default();
+        if !self.objects.view_panel.is_null() {
+            del_panel(self.objects.view_panel);
+            delwin(self.objects.view_win);
+            self.objects.view_panel = NULL;
+            self.objects.view_win = NULL;
+        }
+    }
+}
+
+// a canvas onto a window from its corner
+pub(crate) fn draw_canvas(win: WINDOW, canvas: &Canvas) {
+    werase(win);
+    for y in 0..canvas.height {
+        for x in 0..canvas.width {
+            if let Some(cell) = canvas.get(y, x) {
+                wattrset(win, cell.attr.to_curses());
+                mvwaddstr(win, y, x, cell.ch.encode_utf8(&mut [0; 4]));
+            }
+        }
+    }
+}
+
+fn screen_size() -> (i32, i32) {
+    let mut rows = 0;
+    let mut cols = 0;
+    getmaxyx(stdscr(), &mut rows, &mut cols);
+    (rows, cols)
+}
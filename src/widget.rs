@@ -0,0 +1,185 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+use crate::{
+    domain::{
+        base::{base_art, base_size},
+        canvas::Attr,
+        config::{BaseType, Config},
+        topology::Topology,
+    },
+    grower::Grower,
+};
+
+// a bonsai in a ratatui buffer: the tree grown so far standing on its base at the bottom
+// middle of the area, in the terminal's own colors like the curses ui. pruned branches are
+// left out
+pub struct Bonsai<'a> {
+    topology: &'a Topology,
+    // (rows, cols) the tree was grown for
+    size: (i32, i32),
+    base_type: &'a BaseType,
+}
+
+impl<'a> Bonsai<'a> {
+    // the grower may be part way through, the base comes from the config
+    pub fn new(grower: &'a Grower, config: &'a Config) -> Self {
+        Self {
+            topology: &grower.topology,
+            size: grower.size(),
+            base_type: &config.base_type,
+        }
+    }
+
+    // (rows, cols) to grow a tree in so it fits an area above the base
+    pub fn tree_size(area: Rect, base_type: &BaseType) -> (i32, i32) {
+        let (_, base_height) = base_size(base_type);
+        (i32::from(area.height) - base_height, i32::from(area.width))
+    }
+
+    // color pairs are set up as the first 16 terminal colors on the default background
+    fn style(&self, attr: Attr) -> Style {
+        let style = Style::default().fg(Color::Indexed(attr.color.rem_euclid(16) as u8));
+        if attr.bold {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
+        }
+    }
+
+    // write text with its top left corner at (y, x) of the area, clipping to the area
+    fn put_str(&self, buf: &mut Buffer, area: Rect, y: i32, x: i32, text: &str, attr: Attr) {
+        let style = self.style(attr);
+        for (ch, x) in text.chars().zip(x..) {
+            let (Ok(y), Ok(x)) = (u16::try_from(y), u16::try_from(x)) else {
+                continue;
+            };
+            if y < area.height && x < area.width {
+                buf[(area.x + x, area.y + y)].set_char(ch).set_style(style);
+            }
+        }
+    }
+}
+
+impl Widget for Bonsai<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+impl Widget for &Bonsai<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+        let (rows, cols) = (i32::from(area.height), i32::from(area.width));
+        let (base_width, base_height) = base_size(self.base_type);
+
+        // the tree keeps its trunk on top of the base, even in an area of another size
+        let (tree_rows, tree_cols) = self.size;
+        let offset_y = rows - base_height - tree_rows;
+        let offset_x = cols / 2 - tree_cols / 2;
+        for (_, segment) in self.topology.drawn() {
+            // like the tree window, nothing grows into the base
+            if segment.y < tree_rows {
+                let (y, x) = (segment.y + offset_y, segment.x + offset_x);
+                self.put_str(buf, area, y, x, &segment.text, segment.attr);
+            }
+        }
+
+        let (base_y, base_x) = (rows - base_height, cols / 2 - base_width / 2);
+        for &(y, x, text, attr) in base_art(self.base_type) {
+            self.put_str(buf, area, base_y + y, base_x + x, text, attr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small() -> Config {
+        Config {
+            base_type: BaseType::Small,
+            seed: 7,
+            ..Config::default()
+        }
+    }
+
+    fn render(grower: &Grower, config: &Config, area: Rect) -> Buffer {
+        let mut buf = Buffer::empty(area);
+        Bonsai::new(grower, config).render(area, &mut buf);
+        buf
+    }
+
+    #[test]
+    fn stands_on_its_base_at_the_bottom_middle() {
+        let config = small();
+        let area = Rect::new(0, 0, 40, 20);
+        let (rows, cols) = Bonsai::tree_size(area, &config.base_type);
+        assert_eq!((rows, cols), (17, 40));
+        let buf = render(&Grower::new(&config, rows, cols), &config, area);
+
+        // the small base is 15 wide and 3 high, centered on column 20. buffers go by (x, y)
+        let pot = &buf[(13, 17)];
+        assert_eq!(pot.symbol(), "(");
+        assert_eq!(pot.fg, Color::Indexed(8));
+        assert_eq!(buf[(17, 17)].symbol(), ".");
+        assert_eq!(buf[(17, 17)].fg, Color::Indexed(11));
+        assert_eq!(buf[(14, 18)].symbol(), "(");
+        assert_eq!(buf[(15, 19)].symbol(), "(");
+    }
+
+    #[test]
+    fn draws_the_tree_in_terminal_colors() {
+        let config = small();
+        let area = Rect::new(0, 0, 40, 20);
+        let (rows, cols) = Bonsai::tree_size(area, &config.base_type);
+        let mut grower = Grower::new(&config, rows, cols);
+        while !grower.is_done() {
+            grower.step();
+        }
+        let buf = render(&grower, &config, area);
+
+        let mut drawn = 0;
+        for (_, segment) in grower.topology.drawn() {
+            let inside = |x| (0..cols).contains(&x) && (0..rows).contains(&segment.y);
+            for (ch, x) in segment.text.chars().zip(segment.x..) {
+                if !inside(x) {
+                    continue;
+                }
+                let cell = &buf[(x as u16, segment.y as u16)];
+                if cell.symbol() == ch.to_string() {
+                    drawn += 1;
+                    assert!(matches!(cell.fg, Color::Indexed(0..=15)));
+                }
+            }
+        }
+        assert!(drawn > 0);
+        assert!(buf
+            .content()
+            .iter()
+            .all(|cell| !matches!(cell.fg, Color::Rgb(..))));
+    }
+
+    #[test]
+    fn clips_to_a_smaller_area() {
+        let config = small();
+        let grower = Grower::new(&config, 17, 40);
+        // the tree was grown for more room than it gets, nothing lands outside the area
+        let area = Rect::new(2, 1, 20, 8);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 30, 12));
+        Bonsai::new(&grower, &config).render(area, &mut buf);
+        for y in 0..12 {
+            for x in 0..30 {
+                let inside = (1..9).contains(&y) && (2..22).contains(&x);
+                if !inside {
+                    assert_eq!(buf[(x, y)].symbol(), " ");
+                }
+            }
+        }
+        assert_eq!(buf[(2 + 10 - 7, 1 + 5)].symbol(), "(");
+    }
+}